@@ -0,0 +1,265 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::AnimationDecoder;
+use image::DynamicImage;
+use image::ImageFormat;
+use image::ImageReader;
+use image::ImageResult;
+
+use crate::config;
+
+/// Frames shorter than this are shown for [`DEFAULT_DELAY`] instead, like browsers do, since a lot
+/// of files in the wild rely on it.
+const MIN_DELAY: Duration = Duration::from_millis(20);
+
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+pub struct Frame {
+    pub image: DynamicImage,
+    /// How long the frame stays on screen.
+    pub delay: Duration,
+}
+
+/// Decodes every frame of an animated GIF, PNG or WebP file, other images give a single frame.
+pub fn decode(path: impl AsRef<Path>) -> ImageResult<Vec<Frame>> {
+    let path = path.as_ref();
+    let format = ImageReader::open(path)?.with_guessed_format()?.format();
+    let reader = || File::open(path).map(BufReader::new);
+
+    let frames = match format {
+        Some(ImageFormat::Gif) => GifDecoder::new(reader()?)?.into_frames(),
+
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(reader()?)?;
+            match decoder.is_apng()? {
+                true => decoder.apng()?.into_frames(),
+                false => return single_frame(path),
+            }
+        }
+
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(reader()?)?;
+            match decoder.has_animation() {
+                true => decoder.into_frames(),
+                false => return single_frame(path),
+            }
+        }
+
+        _ => return single_frame(path),
+    };
+
+    frames
+        .map(|frame| {
+            let frame = frame?;
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            let delay = Duration::from_millis(u64::from(numerator / denominator.max(1)));
+
+            Ok(Frame {
+                image: DynamicImage::ImageRgba8(frame.into_buffer()),
+                delay: match delay < MIN_DELAY {
+                    true => DEFAULT_DELAY,
+                    false => delay,
+                },
+            })
+        })
+        .collect()
+}
+
+fn single_frame(path: &Path) -> ImageResult<Vec<Frame>> {
+    let image = ImageReader::open(path)?.with_guessed_format()?.decode()?;
+
+    Ok(vec![Frame {
+        image,
+        delay: Duration::ZERO,
+    }])
+}
+
+/// Keeps track of which frame of an animation is on screen and when the next one is due.
+pub struct Playback {
+    delays: Vec<Duration>,
+    current: usize,
+    loops: Option<u32>,
+    completed_loops: u32,
+    /// When the next frame is due, `None` once the animation stopped or while it is paused.
+    next: Option<Instant>,
+    /// Time left on the current frame when the animation was paused.
+    paused: Option<Duration>,
+    pause_when_hidden: bool,
+}
+
+impl Playback {
+    pub fn new(delays: Vec<Duration>, config: config::Animation) -> Self {
+        let next = match delays.len() {
+            0 | 1 => None,
+            _ => Some(Instant::now() + delays[0]),
+        };
+
+        Self {
+            delays,
+            current: 0,
+            loops: config.loops.filter(|&loops| loops > 0),
+            completed_loops: 0,
+            next,
+            paused: None,
+            pause_when_hidden: config.pause_when_hidden,
+        }
+    }
+
+    /// Index of the frame to draw.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// When the animation next needs [`Playback::advance`] to be called.
+    pub fn next_tick(&self) -> Option<Instant> {
+        self.next
+    }
+
+    /// Moves past every frame that is due, returning whether the frame to draw changed.
+    pub fn advance(&mut self, now: Instant) -> bool {
+        let previous = self.current;
+
+        while let Some(next) = self.next.filter(|&next| next <= now) {
+            if self.current + 1 == self.delays.len() {
+                self.completed_loops += 1;
+
+                if self
+                    .loops
+                    .is_some_and(|loops| self.completed_loops >= loops)
+                {
+                    self.next = None;
+                    break;
+                }
+            }
+
+            self.current = (self.current + 1) % self.delays.len();
+
+            // Fall back to the current time when a frame is very late, e.g. after a suspend,
+            // instead of racing through every frame that was missed.
+            let late = now.duration_since(next) > self.delays[self.current];
+            self.next = Some(match late {
+                true => now + self.delays[self.current],
+                false => next + self.delays[self.current],
+            });
+        }
+
+        self.current != previous
+    }
+
+    /// Pauses or resumes the animation when the window is hidden or shown again, if configured.
+    pub fn set_hidden(&mut self, hidden: bool, now: Instant) {
+        if !self.pause_when_hidden {
+            return;
+        }
+
+        match hidden {
+            true => {
+                if let Some(next) = self.next.take() {
+                    self.paused = Some(next.saturating_duration_since(now));
+                }
+            }
+            false => {
+                if let Some(remaining) = self.paused.take() {
+                    self.next = Some(now + remaining);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn playback(delays: &[u64], loops: Option<u32>) -> Playback {
+        let delays = delays.iter().map(|&delay| MS * delay as u32).collect();
+
+        Playback::new(
+            delays,
+            config::Animation {
+                loops,
+                pause_when_hidden: true,
+            },
+        )
+    }
+
+    #[test]
+    fn frames_follow_their_delays_and_loop() {
+        let mut playback = playback(&[100, 200, 300], None);
+        let start = playback.next_tick().unwrap();
+
+        assert!(!playback.advance(start - MS));
+        assert!(playback.advance(start));
+        assert_eq!(playback.current(), 1);
+        assert_eq!(playback.next_tick(), Some(start + MS * 200));
+
+        assert!(playback.advance(start + MS * 500));
+        assert_eq!(playback.current(), 0);
+        assert_eq!(playback.next_tick(), Some(start + MS * 600));
+    }
+
+    #[test]
+    fn late_frames_are_not_caught_up_on() {
+        let mut playback = playback(&[100, 200, 300], None);
+        let now = playback.next_tick().unwrap() + Duration::from_secs(10);
+
+        assert!(playback.advance(now));
+        assert_eq!(playback.current(), 1);
+        assert_eq!(playback.next_tick(), Some(now + MS * 200));
+    }
+
+    #[test]
+    fn playback_stops_on_the_last_frame_after_its_loops() {
+        let mut playback = playback(&[100, 100], Some(2));
+        let start = playback.next_tick().unwrap();
+
+        for frame in 0..3 {
+            assert!(playback.advance(start + MS * 100 * frame));
+        }
+        assert_eq!(playback.current(), 1);
+
+        assert!(!playback.advance(start + MS * 300));
+        assert_eq!(playback.current(), 1);
+        assert_eq!(playback.next_tick(), None);
+    }
+
+    #[test]
+    fn zero_loops_play_forever() {
+        let mut playback = playback(&[100, 100], Some(0));
+        let start = playback.next_tick().unwrap();
+
+        for frame in 0..10 {
+            assert!(playback.advance(start + MS * 100 * frame));
+        }
+        assert!(playback.next_tick().is_some());
+    }
+
+    #[test]
+    fn hidden_playback_resumes_where_it_paused() {
+        let mut playback = playback(&[100, 200], None);
+        let start = playback.next_tick().unwrap();
+
+        playback.set_hidden(true, start - MS * 40);
+        assert_eq!(playback.next_tick(), None);
+        assert!(!playback.advance(start + Duration::from_secs(5)));
+
+        let shown = start + Duration::from_secs(10);
+        playback.set_hidden(false, shown);
+        assert_eq!(playback.next_tick(), Some(shown + MS * 40));
+        assert_eq!(playback.current(), 0);
+    }
+
+    #[test]
+    fn still_images_never_tick() {
+        assert_eq!(playback(&[0], None).next_tick(), None);
+    }
+}
@@ -1,14 +1,17 @@
-use std::borrow::BorrowMut;
 use std::error::Error;
+use std::time::Instant;
 
 use thiserror::Error;
 use winit::application::ApplicationHandler;
+use winit::dpi::PhysicalPosition;
+use winit::event::StartCause;
 use winit::event::WindowEvent;
 use winit::event_loop::ActiveEventLoop;
 use winit::event_loop::ControlFlow;
 use winit::event_loop::EventLoop;
 use winit::keyboard::Key;
 use winit::keyboard::ModifiersState;
+use winit::keyboard::NamedKey;
 use winit::platform::startup_notify::EventLoopExtStartupNotify;
 use winit::platform::startup_notify::WindowAttributesExtStartupNotify;
 use winit::window::Fullscreen;
@@ -17,14 +20,16 @@ use winit::window::WindowId;
 
 use crate::background::Background;
 use crate::background::BackgroundConversionError;
-use crate::config;
 use crate::config::ConfigError;
 use crate::config::Configuration;
+use crate::layout::LayoutError;
 use crate::render::DrawError;
 use crate::render::Drawable;
-use crate::widget::clock::Clock;
-use crate::widget::date::Date;
-use crate::widget::text::Text;
+use crate::widget;
+use crate::widget::is_media_key;
+use crate::widget::relative::Relations;
+use crate::widget::relative::Scene;
+use crate::widget::Widget;
 use crate::widget::WidgetError;
 use crate::window::WindowState;
 
@@ -32,7 +37,17 @@ pub struct Application {
     window: Option<WindowState>,
 
     background: Background,
-    widgets: Vec<Box<dyn Drawable>>,
+    /// In the order they are listed in the configuration, which is the order they are drawn and
+    /// focused in.
+    widgets: Vec<Box<dyn Widget>>,
+    relations: Relations,
+
+    /// Index of the widget receiving keyboard events.
+    focused: Option<usize>,
+    /// Whether the focused widget is outlined, which it is once focus is moved with the keyboard.
+    show_focus: bool,
+    /// Last known cursor position, used to route pointer events.
+    cursor_position: Option<PhysicalPosition<f64>>,
 }
 
 impl Application {
@@ -41,30 +56,42 @@ impl Application {
 
         let background = (&config.background).try_into()?;
 
-        fn cast_box<W: Drawable + 'static>(widget: W) -> Box<dyn Drawable> {
-            let widget: Box<dyn Drawable> = Box::new(widget);
-            widget
-        }
-
-        let widgets: Vec<Box<dyn Drawable>> = config
-            .widgets
+        let (widgets, relations) = Relations::arrange(config.widgets)?;
+        let widgets = widgets
             .into_iter()
-            .map(|widget| match widget {
-                config::Widget::Clock(config) => Clock::new(event_loop, config).map(cast_box),
-
-                config::Widget::Text(config) => Text::new(config).map(cast_box),
+            .map(|widget| widget::from_config(event_loop, widget))
+            .collect::<Result<Vec<_>, WidgetError>>()?;
 
-                config::Widget::Date(config) => Date::new(event_loop, config).map(cast_box),
-            })
-            .collect::<Result<Vec<Box<_>>, WidgetError>>()?;
+        let focused = widgets.iter().position(|widget| widget.focusable());
 
         Ok(Self {
             window: None,
             background,
             widgets,
+            relations,
+            focused,
+            show_focus: false,
+            cursor_position: None,
         })
     }
 
+    /// Lets the background and widgets catch up with the current time and redraws the window.
+    fn tick(&mut self) {
+        let now = Instant::now();
+
+        let background_changed = self.background.on_tick(now);
+        for widget in self.widgets.iter_mut() {
+            if background_changed {
+                widget.on_background_change();
+            }
+            widget.on_tick(now);
+        }
+
+        if let Some(ref state) = self.window {
+            state.window.request_redraw();
+        }
+    }
+
     fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<WindowId, Box<dyn Error>> {
         let mut window_attributes = WindowAttributes::default();
 
@@ -86,12 +113,17 @@ impl Application {
 }
 
 impl ApplicationHandler for Application {
-    fn user_event(&mut self, _event_loop: &ActiveEventLoop, _event: ()) {
-        if let Some(ref state) = self.window {
-            state.window.request_redraw();
+    fn new_events(&mut self, _event_loop: &ActiveEventLoop, cause: StartCause) {
+        // Woken up because an animation asked for it in `about_to_wait`.
+        if let StartCause::ResumeTimeReached { .. } = cause {
+            self.tick();
         }
     }
 
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, _event: ()) {
+        self.tick();
+    }
+
     fn window_event(
         &mut self,
         event_loop: &ActiveEventLoop,
@@ -109,25 +141,41 @@ impl ApplicationHandler for Application {
             WindowEvent::Resized(size) => {
                 self.background.resize(size);
                 window.resize(size);
+
+                for widget in self.widgets.iter_mut() {
+                    widget.on_resize(size);
+                }
             }
 
             WindowEvent::CloseRequested => {
                 self.window = None;
             }
 
+            WindowEvent::Occluded(hidden) => {
+                self.background.set_hidden(hidden, Instant::now());
+
+                for widget in self.widgets.iter_mut() {
+                    widget.on_event(&event);
+                }
+            }
+
             WindowEvent::ModifiersChanged(modifiers) => {
                 window.modifiers = modifiers.state();
+
+                for widget in self.widgets.iter_mut() {
+                    widget.on_event(&event);
+                }
             }
 
             WindowEvent::RedrawRequested => {
                 let background: &mut dyn Drawable = &mut self.background;
+                let mut scene = Scene {
+                    widgets: &mut self.widgets,
+                    relations: &mut self.relations,
+                    focused: self.focused.filter(|_| self.show_focus),
+                };
 
-                let drawables = self.widgets.iter_mut().map(|widget| {
-                    let widget: &mut dyn Drawable = widget.borrow_mut();
-                    widget
-                });
-
-                let drawables = [background].into_iter().chain(drawables).collect();
+                let drawables = vec![background, &mut scene];
 
                 if let Err(err) = window.draw(drawables) {
                     println!("Error drawing window: {err}");
@@ -135,19 +183,94 @@ impl ApplicationHandler for Application {
             }
 
             WindowEvent::KeyboardInput {
-                event,
+                event: ref key_event,
                 is_synthetic: false,
                 ..
             } => {
                 let mods = window.modifiers;
 
-                if event.state.is_pressed() {
+                if key_event.state.is_pressed() {
                     // TODO make keybinding to close configurable
-                    if let Key::Character(ch) = event.logical_key.as_ref() {
+                    if let Key::Character(ch) = key_event.logical_key.as_ref() {
                         if ch.to_uppercase() == "Q" && mods == ModifiersState::SUPER {
                             event_loop.exit();
+                            return;
                         }
                     }
+
+                    if key_event.logical_key == Key::Named(NamedKey::Tab) && mods.is_empty() {
+                        self.focused = next_focusable(&self.widgets, self.focused);
+                        self.show_focus = true;
+                        window.window.request_redraw();
+                        return;
+                    }
+                }
+
+                // Media keys control whichever widget plays media, even when another one has focus.
+                if is_media_key(&key_event.logical_key) {
+                    let mut redraw = false;
+                    for widget in self.widgets.iter_mut() {
+                        redraw |= widget.on_event(&event);
+                    }
+                    if redraw {
+                        window.window.request_redraw();
+                    }
+                    return;
+                }
+
+                let focused = self.focused.and_then(|index| self.widgets.get_mut(index));
+                if let Some(widget) = focused {
+                    if widget.on_event(&event) {
+                        window.window.request_redraw();
+                    }
+                }
+            }
+
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some(position);
+
+                if let Some(index) = widget_at(&self.relations, position) {
+                    if self.widgets[index].on_event(&event) {
+                        window.window.request_redraw();
+                    }
+                }
+            }
+
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+            }
+
+            WindowEvent::MouseInput { state, .. } => {
+                let Some(index) = self
+                    .cursor_position
+                    .and_then(|position| widget_at(&self.relations, position))
+                else {
+                    return;
+                };
+
+                if state.is_pressed() && self.widgets[index].focusable() {
+                    self.focused = Some(index);
+                    if self.show_focus {
+                        self.show_focus = false;
+                        window.window.request_redraw();
+                    }
+                }
+
+                if self.widgets[index].on_event(&event) {
+                    window.window.request_redraw();
+                }
+            }
+
+            WindowEvent::MouseWheel { .. } => {
+                let Some(index) = self
+                    .cursor_position
+                    .and_then(|position| widget_at(&self.relations, position))
+                else {
+                    return;
+                };
+
+                if self.widgets[index].on_event(&event) {
+                    window.window.request_redraw();
                 }
             }
 
@@ -158,7 +281,20 @@ impl ApplicationHandler for Application {
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
         if self.window.is_none() {
             event_loop.exit();
+            return;
         }
+
+        let next_tick = self
+            .widgets
+            .iter()
+            .filter_map(|widget| widget.next_tick())
+            .chain(self.background.next_tick())
+            .min();
+
+        event_loop.set_control_flow(match next_tick {
+            Some(next_tick) => ControlFlow::WaitUntil(next_tick),
+            None => ControlFlow::Wait,
+        });
     }
 
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
@@ -167,6 +303,24 @@ impl ApplicationHandler for Application {
     }
 }
 
+/// Finds the topmost widget drawn under the given position.
+fn widget_at(relations: &Relations, position: PhysicalPosition<f64>) -> Option<usize> {
+    (0..relations.len()).rposition(|index| {
+        relations
+            .bounds(index)
+            .is_some_and(|bounds| bounds.contains(position.x as f32, position.y as f32))
+    })
+}
+
+/// Finds the focusable widget after `current`, wrapping around the widget list.
+fn next_focusable(widgets: &[Box<dyn Widget>], current: Option<usize>) -> Option<usize> {
+    let start = current.map(|index| index + 1).unwrap_or(0);
+
+    (0..widgets.len())
+        .map(|offset| (start + offset) % widgets.len())
+        .find(|&index| widgets[index].focusable())
+}
+
 #[derive(Debug, Error)]
 pub enum ApplicationError {
     #[error("An error ocurred when parsing the configuration file: {0}")]
@@ -175,6 +329,9 @@ pub enum ApplicationError {
     #[error("An error occurred when converting the background: {0}")]
     BackgroundConversion(#[from] BackgroundConversionError),
 
+    #[error("An error occurred when laying out the widgets: {0}")]
+    Layout(#[from] LayoutError),
+
     #[error("An error ocurrend creating the widget: {0}")]
     Widget(#[from] WidgetError),
 
@@ -1,4 +1,6 @@
 use std::io;
+use std::time::Duration;
+use std::time::Instant;
 
 use fast_image_resize::images::Image;
 use fast_image_resize::CropBox;
@@ -8,7 +10,6 @@ use fast_image_resize::ResizeOptions;
 use fast_image_resize::Resizer;
 use fast_image_resize::SrcCropping;
 use image::DynamicImage;
-use image::ImageReader;
 use image::Luma;
 use image::LumaA;
 use image::Pixel;
@@ -17,19 +18,37 @@ use image::Rgba;
 use num_traits::NumCast;
 use num_traits::ToPrimitive;
 use thiserror::Error;
+use tiny_skia::BlendMode;
 use tiny_skia::Color;
+use tiny_skia::ColorU8;
+use tiny_skia::FilterQuality;
+use tiny_skia::IntSize;
 use tiny_skia::Pixmap;
+use tiny_skia::PixmapPaint;
+use tiny_skia::Transform;
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
 
+use crate::animation;
+use crate::animation::Frame;
+use crate::animation::Playback;
 use crate::config;
+use crate::config::Filters;
+use crate::config::KenBurns;
+use crate::config::PanDirection;
+use crate::effects;
 use crate::render::DrawError;
 use crate::render::Drawable;
 
 pub enum Background {
     Image {
-        image: DynamicImage,
-        resized_image_buffer: Vec<u8>,
+        frames: Vec<Frame>,
+        /// Every frame resized to the window, or larger when panning and zooming over it, so
+        /// playing the animation doesn't need to resize anything.
+        resized_frames: Vec<Pixmap>,
+        playback: Playback,
+        filters: Filters,
+        motion: Option<Motion>,
     },
     Color(Color),
 }
@@ -37,60 +56,292 @@ pub enum Background {
 impl Background {
     pub fn resize(&mut self, size: PhysicalSize<u32>) {
         let Self::Image {
-            image,
-            resized_image_buffer,
+            frames,
+            resized_frames,
+            filters,
+            motion,
+            ..
         } = self
         else {
             return;
         };
 
-        let mut resized = Image::new(size.width, size.height, image.pixel_type().unwrap());
-        let mut resizer = Resizer::new();
-        resizer
-            .resize(
-                image,
-                &mut resized,
-                Some(&ResizeOptions {
-                    cropping: SrcCropping::Crop(CropBox::fit_src_into_dst_size(
-                        image.width() as _,
-                        image.height() as _,
-                        size.width as _,
-                        size.height as _,
-                        Some((0.5, 0.5)),
-                    )),
-                    ..Default::default()
-                }),
-            )
-            .unwrap();
-
-        // The image contains width * height number of pixels, where each pixel is 32-bit long
-        // this way we need a buffer of width * height * 4 (8bit for red, green, blue and alpha)
-        let mut rgba_data = Vec::with_capacity((size.width * size.height * 4) as usize);
-
-        let resized_pixel_type = resized.pixel_type();
-
-        //for pixel in resized.pixels() {
-        for pixel in resized.buffer().chunks_exact(resized_pixel_type.size()) {
-            let [red, green, blue, alpha] = cast_pixel_to_rgba_u8(pixel, resized_pixel_type).0;
-
-            rgba_data.push(red);
-            rgba_data.push(green);
-            rgba_data.push(blue);
-            rgba_data.push(alpha);
+        *resized_frames = frames
+            .iter()
+            .filter_map(|frame| {
+                let (size, scale) = match motion {
+                    Some(motion) => (motion.source_size(&frame.image, size), motion.max_zoom()),
+                    None => (size, 1.0),
+                };
+
+                apply_filters(resize_image(&frame.image, size), size, filters, scale)
+            })
+            .collect();
+    }
+
+    /// When the background next needs [`Background::on_tick`] to be called.
+    pub fn next_tick(&self) -> Option<Instant> {
+        match self {
+            Background::Image {
+                playback, motion, ..
+            } => playback
+                .next_tick()
+                .into_iter()
+                .chain(motion.as_ref().and_then(Motion::next_tick))
+                .min(),
+            Background::Color(_) => None,
+        }
+    }
+
+    /// Moves the animation along, returning whether the background looks different.
+    pub fn on_tick(&mut self, now: Instant) -> bool {
+        match self {
+            Background::Image {
+                playback, motion, ..
+            } => {
+                let new_frame = playback.advance(now);
+                let moved = motion.as_mut().is_some_and(|motion| motion.on_tick(now));
+
+                new_frame || moved
+            }
+            Background::Color(_) => false,
+        }
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool, now: Instant) {
+        if let Background::Image {
+            playback, motion, ..
+        } = self
+        {
+            playback.set_hidden(hidden, now);
+
+            if let Some(motion) = motion {
+                motion.set_hidden(hidden, now);
+            }
+        }
+    }
+}
+
+/// Keeps track of the pan and zoom animation, see [`KenBurns`].
+pub struct Motion {
+    config: KenBurns,
+    /// How far into the animation it is, time only counts while it isn't paused.
+    elapsed: Duration,
+    last_tick: Instant,
+    paused: bool,
+    pause_when_hidden: bool,
+}
+
+impl Motion {
+    fn new(config: KenBurns, pause_when_hidden: bool) -> Self {
+        Self {
+            config,
+            elapsed: Duration::ZERO,
+            last_tick: Instant::now(),
+            paused: false,
+            pause_when_hidden,
+        }
+    }
+
+    /// Zoom at the start and end of a pass, never below covering the window.
+    fn zooms(&self) -> (f32, f32) {
+        (self.config.zoom_from.max(1.0), self.config.zoom_to.max(1.0))
+    }
+
+    fn max_zoom(&self) -> f32 {
+        let (from, to) = self.zooms();
+        from.max(to)
+    }
+
+    /// Size the image is kept at, large enough to be shown at the highest zoom without being
+    /// scaled up.
+    fn source_size(&self, image: &DynamicImage, window: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let cover = (window.width as f32 / image.width() as f32)
+            .max(window.height as f32 / image.height() as f32);
+        let scale = cover * self.max_zoom();
+
+        PhysicalSize::new(
+            (image.width() as f32 * scale).ceil() as u32,
+            (image.height() as f32 * scale).ceil() as u32,
+        )
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        let interval = Duration::from_secs(1) / self.config.fps.max(1);
+
+        (!self.paused).then(|| self.last_tick + interval)
+    }
+
+    /// Returns whether the image moved.
+    fn on_tick(&mut self, now: Instant) -> bool {
+        if !self.paused {
+            self.elapsed += now.saturating_duration_since(self.last_tick);
+        }
+        self.last_tick = now;
+
+        !self.paused
+    }
+
+    fn set_hidden(&mut self, hidden: bool, now: Instant) {
+        if !self.pause_when_hidden {
+            return;
         }
 
-        *resized_image_buffer = rgba_data;
+        self.on_tick(now);
+        self.paused = hidden;
+    }
+
+    /// Maps the source image to the window for the current point of the animation.
+    fn transform(&self, window: (f32, f32), source: (f32, f32)) -> Transform {
+        let duration = self.config.duration.max(1) as f32;
+        let passes = self.elapsed.as_secs_f32() / duration;
+
+        // Every other pass runs backwards, and each one eases in and out.
+        let progress = match passes as u64 % 2 {
+            0 => passes.fract(),
+            _ => 1.0 - passes.fract(),
+        };
+        let progress = progress * progress * (3.0 - 2.0 * progress);
+
+        let (from, to) = self.zooms();
+        let zoom = from + (to - from) * progress;
+        let scale = zoom / self.max_zoom();
+
+        // How far the visible part of the source can move inside it.
+        let free_x = (source.0 - window.0 / scale).max(0.0);
+        let free_y = (source.1 - window.1 / scale).max(0.0);
+
+        let (pan_x, pan_y) = match self.config.direction {
+            PanDirection::Center => (0.5, 0.5),
+            PanDirection::LeftToRight => (progress, 0.5),
+            PanDirection::RightToLeft => (1.0 - progress, 0.5),
+            PanDirection::TopToBottom => (0.5, progress),
+            PanDirection::BottomToTop => (0.5, 1.0 - progress),
+            PanDirection::TopLeftToBottomRight => (progress, progress),
+            PanDirection::BottomRightToTopLeft => (1.0 - progress, 1.0 - progress),
+        };
+
+        Transform::from_row(
+            scale,
+            0.0,
+            0.0,
+            scale,
+            -free_x * pan_x * scale,
+            -free_y * pan_y * scale,
+        )
+    }
+}
+
+/// Scales `image` to cover `size`, returning premultiplied RGBA pixels.
+fn resize_image(image: &DynamicImage, size: PhysicalSize<u32>) -> Vec<u8> {
+    let mut resized = Image::new(size.width, size.height, image.pixel_type().unwrap());
+    let mut resizer = Resizer::new();
+    resizer
+        .resize(
+            image,
+            &mut resized,
+            Some(&ResizeOptions {
+                cropping: SrcCropping::Crop(CropBox::fit_src_into_dst_size(
+                    image.width() as _,
+                    image.height() as _,
+                    size.width as _,
+                    size.height as _,
+                    Some((0.5, 0.5)),
+                )),
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+    // The image contains width * height number of pixels, where each pixel is 32-bit long
+    // this way we need a buffer of width * height * 4 (8bit for red, green, blue and alpha)
+    let mut rgba_data = Vec::with_capacity((size.width * size.height * 4) as usize);
+
+    let resized_pixel_type = resized.pixel_type();
+
+    // tiny-skia and the filters work on premultiplied colors.
+    for pixel in resized.buffer().chunks_exact(resized_pixel_type.size()) {
+        let [red, green, blue, alpha] = cast_pixel_to_rgba_u8(pixel, resized_pixel_type).0;
+        let pixel = ColorU8::from_rgba(red, green, blue, alpha).premultiply();
+
+        rgba_data.push(pixel.red());
+        rgba_data.push(pixel.green());
+        rgba_data.push(pixel.blue());
+        rgba_data.push(pixel.alpha());
+    }
+
+    rgba_data
+}
+
+/// Runs the configured filters over a resized frame, so they are paid for once per resize.
+///
+/// `scale` is how much larger than the window the frame is, the blur grows along with it so it
+/// looks the same once the frame is scaled down.
+fn apply_filters(
+    frame: Vec<u8>,
+    size: PhysicalSize<u32>,
+    filters: &Filters,
+    scale: f32,
+) -> Option<Pixmap> {
+    let size = IntSize::from_wh(size.width, size.height)?;
+    let mut pixmap = Pixmap::from_vec(frame, size)?;
+
+    effects::blur(&mut pixmap, filters.blur * scale);
+    if filters.brightness != 1.0 {
+        effects::brightness(&mut pixmap, filters.brightness);
+    }
+    match filters.grayscale {
+        true => effects::saturation(&mut pixmap, 0.0),
+        false if filters.saturation != 1.0 => effects::saturation(&mut pixmap, filters.saturation),
+        false => {}
+    }
+    if let Some(tint) = filters.tint {
+        effects::tint(&mut pixmap, tint);
     }
+
+    Some(pixmap)
 }
 
 impl Drawable for Background {
     fn draw(&mut self, _window: &Window, buffer: &mut Pixmap) -> Result<(), DrawError> {
         match self {
             Background::Image {
-                image: _,
-                resized_image_buffer,
+                resized_frames,
+                playback,
+                motion,
+                ..
             } => {
-                buffer.data_mut().copy_from_slice(resized_image_buffer);
+                let Some(frame) = resized_frames.get(playback.current()) else {
+                    return Ok(());
+                };
+
+                match motion {
+                    Some(motion) => {
+                        let transform = motion.transform(
+                            (buffer.width() as f32, buffer.height() as f32),
+                            (frame.width() as f32, frame.height() as f32),
+                        );
+
+                        buffer.draw_pixmap(
+                            0,
+                            0,
+                            frame.as_ref(),
+                            &PixmapPaint {
+                                blend_mode: BlendMode::Source,
+                                quality: FilterQuality::Bilinear,
+                                ..Default::default()
+                            },
+                            transform,
+                            None,
+                        );
+                    }
+
+                    None if frame.data().len() == buffer.data().len() => {
+                        buffer.data_mut().copy_from_slice(frame.data());
+                    }
+
+                    None => {}
+                }
             }
 
             Background::Color(color) => {
@@ -107,15 +358,21 @@ impl TryFrom<&config::Background> for Background {
 
     fn try_from(value: &config::Background) -> Result<Self, Self::Error> {
         match value {
-            config::Background::Image(path) => {
-                let image = ImageReader::open(path)?.decode()?;
+            config::Background::Image(image) => {
+                let frames = animation::decode(image.path())?;
+                let delays = frames.iter().map(|frame| frame.delay).collect();
 
                 Ok(Self::Image {
-                    image,
-                    // that's an "optimization" that instead of crating the resized_image_buffer from
-                    // the original image and rewriting it after, we just ignore it here so it can
-                    // be populated after. its pretty dumb, but hey, it works.
-                    resized_image_buffer: Vec::new(),
+                    frames,
+                    // that's an "optimization" that instead of crating the resized frames from
+                    // the original image and rewriting them after, we just ignore it here so
+                    // they can be populated after. its pretty dumb, but hey, it works.
+                    resized_frames: Vec::new(),
+                    playback: Playback::new(delays, image.animation()),
+                    filters: image.filters(),
+                    motion: image
+                        .ken_burns()
+                        .map(|config| Motion::new(config, image.animation().pause_when_hidden)),
                 })
             }
             config::Background::Color((r, g, b)) => {
@@ -285,3 +542,87 @@ fn cast_pixel_to_rgba_u8(pixel: &[u8], pixel_type: PixelType) -> Rgba<u8> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+    use image::RgbaImage;
+
+    use super::*;
+
+    #[test]
+    fn resized_frames_are_premultiplied() {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([255, 100, 0, 128])));
+
+        let frame = resize_image(&image, PhysicalSize::new(2, 2));
+
+        assert_eq!(frame.len(), 2 * 2 * 4);
+        for pixel in frame.chunks_exact(4) {
+            assert_eq!(pixel, [128, 50, 0, 128]);
+        }
+    }
+
+    fn ken_burns(pause_when_hidden: bool, start: Instant) -> Motion {
+        let config = KenBurns {
+            duration: 10,
+            zoom_from: 1.0,
+            zoom_to: 1.2,
+            direction: PanDirection::LeftToRight,
+            fps: 10,
+        };
+
+        Motion {
+            last_tick: start,
+            ..Motion::new(config, pause_when_hidden)
+        }
+    }
+
+    #[test]
+    fn motion_only_moves_while_shown() {
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        let mut motion = ken_burns(true, start);
+
+        assert_eq!(motion.next_tick(), Some(start + second / 10));
+        assert!(motion.on_tick(start + second));
+        assert_eq!(motion.elapsed, second);
+
+        motion.set_hidden(true, start + second * 2);
+        assert_eq!(motion.next_tick(), None);
+        assert!(!motion.on_tick(start + second * 10));
+
+        motion.set_hidden(false, start + second * 20);
+        assert_eq!(motion.elapsed, second * 2);
+        assert_eq!(motion.next_tick(), Some(start + second * 20 + second / 10));
+
+        // Without pausing, hidden time counts too.
+        let mut motion = ken_burns(false, start);
+        motion.set_hidden(true, start + second);
+        assert!(motion.on_tick(start + second * 3));
+        assert_eq!(motion.elapsed, second * 3);
+    }
+
+    #[test]
+    fn motion_pans_and_zooms_back_and_forth() {
+        let start = Instant::now();
+        let mut motion = ken_burns(true, start);
+        // A 100×50 image kept large enough for the highest zoom.
+        let (window, source) = ((100.0, 50.0), (120.0, 60.0));
+
+        let at = |motion: &mut Motion, seconds: u64| {
+            motion.on_tick(start + Duration::from_secs(seconds));
+            let transform = motion.transform(window, source);
+
+            [transform.sx, transform.sy, transform.tx, transform.ty]
+                .map(|value| (value * 1000.0).round() / 1000.0)
+        };
+
+        // The whole source covers the window at first, then the view zooms in while it pans
+        // right, and comes back on the next pass.
+        assert_eq!(at(&mut motion, 0), [0.833, 0.833, 0.0, 0.0]);
+        assert_eq!(at(&mut motion, 5), [0.917, 0.917, -5.0, -2.5]);
+        assert_eq!(at(&mut motion, 10), [1.0, 1.0, -20.0, -5.0]);
+        assert_eq!(at(&mut motion, 15), [0.917, 0.917, -5.0, -2.5]);
+        assert_eq!(at(&mut motion, 20), [0.833, 0.833, 0.0, 0.0]);
+    }
+}
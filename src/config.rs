@@ -2,23 +2,71 @@ use serde::Deserialize;
 use serde::Serialize;
 use thiserror::Error;
 
+mod animation;
 mod background;
 mod font;
+mod shape;
+mod text;
+mod thresholds;
 mod widget;
 
+pub use animation::Animation;
 pub use background::Background;
+pub use background::Filters;
+pub use background::KenBurns;
+pub use background::PanDirection;
 pub use font::Font;
+pub use font::Overflow;
+pub use shape::Backdrop;
+pub use shape::Fill;
+pub use shape::GradientStop;
+pub use shape::StrokeConfig;
+pub use text::TextAlign;
+pub use text::TextLayout;
+pub use text::WrapMode;
+pub use thresholds::Thresholds;
+pub use widget::AgendaConfig;
+pub use widget::BatteryConfig;
+pub use widget::CalendarConfig;
+pub use widget::CircleConfig;
 pub use widget::ClockConfig;
+pub use widget::CommandConfig;
+pub use widget::ContainerConfig;
 pub use widget::DateConfig;
+pub use widget::DiskConfig;
+pub use widget::FileSource;
+pub use widget::FileTextConfig;
+pub use widget::GraphConfig;
+pub use widget::GraphSource;
+pub use widget::GraphStyle;
+pub use widget::GridConfig;
+pub use widget::HttpConfig;
+pub use widget::ImageConfig;
+pub use widget::LineConfig;
+pub use widget::MeterConfig;
+pub use widget::MonitorDisplay;
+pub use widget::NetworkConfig;
+pub use widget::NowPlayingConfig;
+pub use widget::PanelConfig;
+pub use widget::PomodoroConfig;
+pub use widget::PomodoroKeys;
+pub use widget::RectangleConfig;
+pub use widget::StackConfig;
+pub use widget::SystemMonitorConfig;
+pub use widget::TemperatureConfig;
 pub use widget::TextConfig;
+pub use widget::TimerMode;
+pub use widget::WeatherConfig;
+pub use widget::WeekStart;
 pub use widget::Widget;
+pub use widget::WidgetEntry;
 
 use crate::widget::Position;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Configuration {
     pub background: Background,
-    pub widgets: Vec<Widget>,
+    pub widgets: Vec<WidgetEntry>,
 }
 
 impl Configuration {
@@ -31,16 +79,21 @@ impl Default for Configuration {
     fn default() -> Self {
         Configuration {
             background: Background::Color((0, 0, 0)),
-            widgets: vec![Widget::Clock(ClockConfig {
-                show_seconds: false,
-                position: Position::XY { x: 50, y: 100 },
-                font: Font {
-                    font_size: 150.0,
-                    line_height: 1.0,
-                    font_family: None,
-                    font_weight: 100,
-                },
-            })],
+            widgets: vec![WidgetEntry {
+                id: None,
+                widget: Widget::Clock(ClockConfig {
+                    show_seconds: false,
+                    position: Position::XY { x: 50, y: 100 },
+                    font: Font {
+                        font_size: 150.0,
+                        line_height: 1.0,
+                        font_family: None,
+                        font_weight: 100,
+                        color: (255, 255, 255, 100),
+                        overflow: Overflow::Clip,
+                    },
+                }),
+            }],
         }
     }
 }
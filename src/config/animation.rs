@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How animated images (GIF, APNG and WebP) are played.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Animation {
+    /// How many times the animation plays before stopping on its last frame. It plays forever
+    /// when unset or 0, as 0 means forever in GIF files too.
+    pub loops: Option<u32>,
+    /// Stop the animation while the window is not visible.
+    #[serde(default = "default_pause_when_hidden")]
+    pub pause_when_hidden: bool,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Self {
+            loops: None,
+            pause_when_hidden: default_pause_when_hidden(),
+        }
+    }
+}
+
+fn default_pause_when_hidden() -> bool {
+    true
+}
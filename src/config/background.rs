@@ -1,8 +1,136 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::Animation;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Background {
-    Image(String),
+    Image(BackgroundImage),
     Color((u8, u8, u8)),
 }
+
+/// An image background, given either as just its path or as a table with more options.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BackgroundImage {
+    Path(String),
+    Options {
+        path: String,
+        #[serde(flatten)]
+        animation: Animation,
+        #[serde(flatten)]
+        filters: Filters,
+        /// Slowly pans and zooms over the image.
+        ken_burns: Option<KenBurns>,
+    },
+}
+
+/// Adjustments made to an image background once it is resized to the window.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Filters {
+    /// Strength of the blur, as the standard deviation in pixels like CSS `blur()`.
+    #[serde(default)]
+    pub blur: f32,
+    /// Factor the colors are multiplied by, below 1 dims the image.
+    #[serde(default = "default_factor")]
+    pub brightness: f32,
+    /// How colorful the image is, from 0 for gray to 1 for unchanged and above for more vivid.
+    #[serde(default = "default_factor")]
+    pub saturation: f32,
+    /// Same as setting `saturation` to 0.
+    #[serde(default)]
+    pub grayscale: bool,
+    /// Color drawn over the image.
+    pub tint: Option<(u8, u8, u8, u8)>,
+}
+
+impl Default for Filters {
+    fn default() -> Self {
+        Self {
+            blur: 0.0,
+            brightness: default_factor(),
+            saturation: default_factor(),
+            grayscale: false,
+            tint: None,
+        }
+    }
+}
+
+fn default_factor() -> f32 {
+    1.0
+}
+
+impl BackgroundImage {
+    pub fn path(&self) -> &str {
+        match self {
+            BackgroundImage::Path(path) => path,
+            BackgroundImage::Options { path, .. } => path,
+        }
+    }
+
+    pub fn animation(&self) -> Animation {
+        match self {
+            BackgroundImage::Path(_) => Animation::default(),
+            BackgroundImage::Options { animation, .. } => *animation,
+        }
+    }
+
+    pub fn filters(&self) -> Filters {
+        match self {
+            BackgroundImage::Path(_) => Filters::default(),
+            BackgroundImage::Options { filters, .. } => *filters,
+        }
+    }
+
+    pub fn ken_burns(&self) -> Option<KenBurns> {
+        match self {
+            BackgroundImage::Path(_) => None,
+            BackgroundImage::Options { ken_burns, .. } => *ken_burns,
+        }
+    }
+}
+
+/// Pan and zoom animation over an image. The motion goes back and forth so it never jumps.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct KenBurns {
+    /// Seconds a pass over the image takes before the motion turns around.
+    #[serde(default = "default_ken_burns_duration")]
+    pub duration: u64,
+    /// Zoom at the start of a pass, 1 being the image covering the window.
+    #[serde(default = "default_factor")]
+    pub zoom_from: f32,
+    /// Zoom at the end of a pass.
+    #[serde(default = "default_zoom_to")]
+    pub zoom_to: f32,
+    #[serde(default)]
+    pub direction: PanDirection,
+    /// How many times a second the background is redrawn.
+    #[serde(default = "default_ken_burns_fps")]
+    pub fps: u32,
+}
+
+fn default_ken_burns_duration() -> u64 {
+    60
+}
+
+fn default_zoom_to() -> f32 {
+    1.2
+}
+
+fn default_ken_burns_fps() -> u32 {
+    30
+}
+
+/// Which way the view moves over the image during a pass.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum PanDirection {
+    /// Stays centered and only zooms.
+    #[default]
+    Center,
+    LeftToRight,
+    RightToLeft,
+    TopToBottom,
+    BottomToTop,
+    TopLeftToBottomRight,
+    BottomRightToTopLeft,
+}
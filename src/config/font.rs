@@ -7,4 +7,27 @@ pub struct Font {
     pub font_family: Option<String>,
     pub font_size: f32,
     pub font_weight: u16,
+    /// Text color as RGBA.
+    #[serde(default = "default_font_color")]
+    pub color: (u8, u8, u8, u8),
+    /// What happens to text that doesn't fit in the window.
+    #[serde(default)]
+    pub overflow: Overflow,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Overflow {
+    /// Draws the text as it is, cut at the edges of the window.
+    #[default]
+    Clip,
+    /// Makes the font smaller until the text fits.
+    Shrink,
+    /// Breaks lines that are too long, between words when possible.
+    Wrap,
+    /// Cuts lines that are too long and ends them with an ellipsis.
+    Ellipsize,
+}
+
+fn default_font_color() -> (u8, u8, u8, u8) {
+    (255, 255, 255, 100)
 }
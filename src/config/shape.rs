@@ -0,0 +1,53 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How the inside of a shape is painted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Fill {
+    Color((u8, u8, u8, u8)),
+    /// Gradient across the shape, at `angle` degrees clockwise from left to right.
+    LinearGradient {
+        #[serde(default)]
+        angle: f32,
+        stops: Vec<GradientStop>,
+    },
+    /// Gradient from the center of the shape to its furthest edge.
+    RadialGradient {
+        stops: Vec<GradientStop>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GradientStop {
+    /// Where the color is reached, from 0 at the start of the gradient to 1 at its end.
+    pub offset: f32,
+    pub color: (u8, u8, u8, u8),
+}
+
+/// Outline drawn around a shape.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct StrokeConfig {
+    pub width: f32,
+    #[serde(default = "default_stroke_color")]
+    pub color: (u8, u8, u8, u8),
+}
+
+fn default_stroke_color() -> (u8, u8, u8, u8) {
+    (255, 255, 255, 255)
+}
+
+/// Frosted-glass effect: whatever is under a shape is blurred before the shape is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Backdrop {
+    /// Strength of the blur, as the standard deviation in pixels like CSS `blur()`.
+    pub blur: f32,
+    /// Factor the blurred colors are multiplied by, below 1 darkens them.
+    #[serde(default = "default_brightness")]
+    pub brightness: f32,
+    /// Color drawn over the blurred area.
+    pub tint: Option<(u8, u8, u8, u8)>,
+}
+
+fn default_brightness() -> f32 {
+    1.0
+}
@@ -0,0 +1,36 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// How the lines of a text widget are broken and lined up.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TextLayout {
+    /// Width past which lines are broken, in pixels.
+    pub max_width: Option<f32>,
+    /// Height past which lines are left out, in pixels.
+    pub max_height: Option<f32>,
+    #[serde(default)]
+    pub align: TextAlign,
+    #[serde(default)]
+    pub wrap: WrapMode,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches the spaces of every line but the last one of a paragraph to fill the width of
+    /// the text.
+    Justified,
+}
+
+/// Where lines wider than `max_width` are broken.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WrapMode {
+    /// Between words, or anywhere in words too long to fit on their own.
+    #[default]
+    Word,
+    Glyph,
+    None,
+}
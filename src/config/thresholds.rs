@@ -0,0 +1,36 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Values at which a widget changes its text color.
+///
+/// When `critical` is below `warning` lower values are worse (e.g. battery charge), otherwise
+/// higher values are worse (e.g. temperatures).
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Thresholds {
+    pub warning: f32,
+    pub critical: f32,
+    #[serde(default = "default_warning_color")]
+    pub warning_color: (u8, u8, u8, u8),
+    #[serde(default = "default_critical_color")]
+    pub critical_color: (u8, u8, u8, u8),
+}
+
+impl Thresholds {
+    /// Thresholds with the default colors.
+    pub fn new(warning: f32, critical: f32) -> Self {
+        Self {
+            warning,
+            critical,
+            warning_color: default_warning_color(),
+            critical_color: default_critical_color(),
+        }
+    }
+}
+
+fn default_warning_color() -> (u8, u8, u8, u8) {
+    (241, 196, 15, 200)
+}
+
+fn default_critical_color() -> (u8, u8, u8, u8) {
+    (231, 76, 60, 200)
+}
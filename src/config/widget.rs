@@ -1,9 +1,19 @@
+use std::collections::BTreeMap;
+
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::layout::Align;
+use crate::layout::Anchor;
 use crate::widget::Position;
 
+use super::Animation;
+use super::Backdrop;
+use super::Fill;
 use super::Font;
+use super::StrokeConfig;
+use super::TextLayout;
+use super::Thresholds;
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "widget")]
@@ -11,11 +21,98 @@ pub enum Widget {
     Clock(ClockConfig),
     Text(TextConfig),
     Date(DateConfig),
+    Pomodoro(PomodoroConfig),
+    Command(CommandConfig),
+    FileText(FileTextConfig),
+    Battery(BatteryConfig),
+    Cpu(SystemMonitorConfig),
+    Memory(SystemMonitorConfig),
+    LoadAverage(SystemMonitorConfig),
+    Uptime(SystemMonitorConfig),
+    Graph(GraphConfig),
+    Disk(DiskConfig),
+    Temperature(TemperatureConfig),
+    Network(NetworkConfig),
+    Weather(WeatherConfig),
+    Http(HttpConfig),
+    Agenda(AgendaConfig),
+    Calendar(CalendarConfig),
+    NowPlaying(NowPlayingConfig),
+    Image(ImageConfig),
+    Rectangle(RectangleConfig),
+    Circle(CircleConfig),
+    Line(LineConfig),
+    Panel(PanelConfig),
+    Row(ContainerConfig),
+    Column(ContainerConfig),
+    Stack(StackConfig),
+    Grid(GridConfig),
+}
+
+impl Widget {
+    pub fn position(&self) -> &Position {
+        match self {
+            Widget::Clock(ClockConfig { position, .. })
+            | Widget::Text(TextConfig { position, .. })
+            | Widget::Date(DateConfig { position, .. })
+            | Widget::Pomodoro(PomodoroConfig { position, .. })
+            | Widget::Command(CommandConfig { position, .. })
+            | Widget::FileText(FileTextConfig { position, .. })
+            | Widget::Battery(BatteryConfig { position, .. })
+            | Widget::Cpu(SystemMonitorConfig { position, .. })
+            | Widget::Memory(SystemMonitorConfig { position, .. })
+            | Widget::LoadAverage(SystemMonitorConfig { position, .. })
+            | Widget::Uptime(SystemMonitorConfig { position, .. })
+            | Widget::Graph(GraphConfig { position, .. })
+            | Widget::Disk(DiskConfig { position, .. })
+            | Widget::Temperature(TemperatureConfig { position, .. })
+            | Widget::Network(NetworkConfig { position, .. })
+            | Widget::Weather(WeatherConfig { position, .. })
+            | Widget::Http(HttpConfig { position, .. })
+            | Widget::Agenda(AgendaConfig { position, .. })
+            | Widget::Calendar(CalendarConfig { position, .. })
+            | Widget::NowPlaying(NowPlayingConfig { position, .. })
+            | Widget::Image(ImageConfig { position, .. })
+            | Widget::Rectangle(RectangleConfig { position, .. })
+            | Widget::Circle(CircleConfig { position, .. })
+            | Widget::Line(LineConfig { position, .. })
+            | Widget::Panel(PanelConfig { position, .. })
+            | Widget::Row(ContainerConfig { position, .. })
+            | Widget::Column(ContainerConfig { position, .. })
+            | Widget::Stack(StackConfig { position, .. })
+            | Widget::Grid(GridConfig { position, .. }) => position,
+        }
+    }
+
+    /// Widgets laid out inside this one.
+    pub fn children(&self) -> &[Widget] {
+        match self {
+            Widget::Panel(PanelConfig { child, .. }) => child
+                .as_deref()
+                .map_or(&[], |child| std::slice::from_ref(child)),
+            Widget::Row(ContainerConfig { children, .. })
+            | Widget::Column(ContainerConfig { children, .. })
+            | Widget::Stack(StackConfig { children, .. })
+            | Widget::Grid(GridConfig { children, .. }) => children,
+            _ => &[],
+        }
+    }
+}
+
+/// A widget placed directly in the window, which other widgets can be placed relative to when it
+/// has an `id`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WidgetEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(flatten)]
+    pub widget: Widget,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ClockConfig {
     pub show_seconds: bool,
+    #[serde(default)]
     pub position: Position,
     #[serde(flatten)]
     pub font: Font,
@@ -24,13 +121,29 @@ pub struct ClockConfig {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TextConfig {
     pub text: String,
+    #[serde(default)]
     pub position: Position,
     #[serde(flatten)]
     pub font: Font,
+    #[serde(flatten)]
+    pub layout: TextLayout,
+}
+
+impl TextConfig {
+    /// Text without a maximum size or alignment, for widgets that draw their data as text.
+    pub fn new(text: String, position: Position, font: Font) -> Self {
+        Self {
+            text,
+            position,
+            font,
+            layout: TextLayout::default(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DateConfig {
+    #[serde(default)]
     pub position: Position,
     #[serde(default = "default_date_format")]
     pub format: String,
@@ -41,3 +154,713 @@ pub struct DateConfig {
 fn default_date_format() -> String {
     "%A - %B %d".to_string()
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    #[serde(default)]
+    pub position: Position,
+    #[serde(default)]
+    pub mode: TimerMode,
+    /// Length of a work session, in minutes.
+    #[serde(default = "default_work_duration")]
+    pub work_duration: u64,
+    /// Length of a short break, in minutes.
+    #[serde(default = "default_short_break_duration")]
+    pub short_break_duration: u64,
+    /// Length of a long break, in minutes.
+    #[serde(default = "default_long_break_duration")]
+    pub long_break_duration: u64,
+    /// How many work sessions happen before a long break.
+    #[serde(default = "default_cycles_before_long_break")]
+    pub cycles_before_long_break: u32,
+    #[serde(default = "default_ring_radius")]
+    pub ring_radius: f32,
+    #[serde(default = "default_ring_width")]
+    pub ring_width: f32,
+    #[serde(default = "default_work_color")]
+    pub work_color: (u8, u8, u8),
+    #[serde(default = "default_break_color")]
+    pub break_color: (u8, u8, u8),
+    #[serde(default)]
+    pub keys: PomodoroKeys,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_work_duration() -> u64 {
+    25
+}
+
+fn default_short_break_duration() -> u64 {
+    5
+}
+
+fn default_long_break_duration() -> u64 {
+    15
+}
+
+fn default_cycles_before_long_break() -> u32 {
+    4
+}
+
+fn default_ring_radius() -> f32 {
+    150.0
+}
+
+fn default_ring_width() -> f32 {
+    12.0
+}
+
+fn default_work_color() -> (u8, u8, u8) {
+    (231, 76, 60)
+}
+
+fn default_break_color() -> (u8, u8, u8) {
+    (46, 204, 113)
+}
+
+/// What the pomodoro widget times.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimerMode {
+    /// Counts down work sessions and breaks, one after the other.
+    #[default]
+    Pomodoro,
+    /// Counts up from zero until it is reset, the ring goes around once a minute. The durations
+    /// and the skip key are not used.
+    Stopwatch,
+}
+
+/// Keys that control the pomodoro timer, matched against the text the key produces.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PomodoroKeys {
+    pub toggle: String,
+    pub reset: String,
+    pub skip: String,
+}
+
+impl Default for PomodoroKeys {
+    fn default() -> Self {
+        PomodoroKeys {
+            toggle: " ".to_string(),
+            reset: "r".to_string(),
+            skip: "s".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandConfig {
+    /// Shell command, run with `sh -c`.
+    pub command: String,
+    /// Seconds between runs, the command runs only once when not set.
+    pub interval: Option<u64>,
+    /// Seconds the command may run before it is killed.
+    #[serde(default = "default_command_timeout")]
+    pub timeout: u64,
+    /// Only show the last N lines of the output.
+    pub lines: Option<usize>,
+    /// Text color used to show stderr and the exit code when the command fails.
+    #[serde(default = "default_error_color")]
+    pub error_color: (u8, u8, u8, u8),
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_command_timeout() -> u64 {
+    10
+}
+
+fn default_error_color() -> (u8, u8, u8, u8) {
+    (231, 76, 60, 200)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileTextConfig {
+    pub source: FileSource,
+    /// Only keep the last N lines.
+    pub lines: Option<usize>,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum FileSource {
+    /// Regular file, re-read whenever it changes.
+    File(String),
+    /// Named pipe, lines are appended as they are written to it.
+    Fifo(String),
+    /// Lines written to the standard input.
+    Stdin,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatteryConfig {
+    /// Directory holding the power supplies.
+    #[serde(default = "default_power_supply_root")]
+    pub root: String,
+    /// Name of the power supply to show, the first battery found is used when not set.
+    pub name: Option<String>,
+    /// Seconds between reads.
+    #[serde(default = "default_battery_interval")]
+    pub interval: u64,
+    /// Text to show, where `{capacity}`, `{status}` and `{time}` are replaced by the charge
+    /// percentage, the charging state and the estimated time remaining.
+    #[serde(default = "default_battery_format")]
+    pub format: String,
+    #[serde(default = "default_battery_thresholds")]
+    pub thresholds: Thresholds,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_power_supply_root() -> String {
+    "/sys/class/power_supply".to_string()
+}
+
+fn default_battery_interval() -> u64 {
+    30
+}
+
+fn default_battery_format() -> String {
+    "{capacity}% {status} {time}".to_string()
+}
+
+fn default_battery_thresholds() -> Thresholds {
+    Thresholds::new(20.0, 10.0)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SystemMonitorConfig {
+    /// Directory where procfs is mounted.
+    #[serde(default = "default_proc_root")]
+    pub root: String,
+    /// Seconds between samples.
+    #[serde(default = "default_sample_interval")]
+    pub interval: u64,
+    /// Text to show, the placeholders available depend on the metric. The metric's own format is
+    /// used when not set.
+    pub format: Option<String>,
+    #[serde(default)]
+    pub display: MonitorDisplay,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_proc_root() -> String {
+    "/proc".to_string()
+}
+
+fn default_sample_interval() -> u64 {
+    2
+}
+
+/// How a metric is shown, bars and sparklines are drawn below the text.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub enum MonitorDisplay {
+    #[default]
+    Text,
+    Bar(MeterConfig),
+    Sparkline(MeterConfig),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeterConfig {
+    pub width: f32,
+    pub height: f32,
+    #[serde(default = "default_meter_color")]
+    pub color: (u8, u8, u8, u8),
+    /// How many samples the sparkline keeps.
+    #[serde(default = "default_meter_samples")]
+    pub samples: usize,
+}
+
+fn default_meter_color() -> (u8, u8, u8, u8) {
+    (255, 255, 255, 100)
+}
+
+fn default_meter_samples() -> usize {
+    60
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphConfig {
+    pub source: GraphSource,
+    /// Seconds between samples.
+    #[serde(default = "default_sample_interval")]
+    pub interval: u64,
+    /// How many samples are kept and drawn.
+    #[serde(default = "default_meter_samples")]
+    pub samples: usize,
+    #[serde(default)]
+    pub style: GraphStyle,
+    /// Bottom of the vertical axis, the smallest sample when not set.
+    pub min: Option<f32>,
+    /// Top of the vertical axis, the largest sample when not set.
+    pub max: Option<f32>,
+    pub width: f32,
+    pub height: f32,
+    #[serde(default = "default_meter_color")]
+    pub color: (u8, u8, u8, u8),
+    /// Color below the line of area charts, a translucent `color` when not set.
+    pub fill_color: Option<(u8, u8, u8, u8)>,
+    #[serde(default = "default_graph_line_width")]
+    pub line_width: f32,
+    /// Draw lines as curves through the samples instead of straight segments.
+    #[serde(default)]
+    pub smooth: bool,
+    #[serde(default)]
+    pub position: Position,
+}
+
+fn default_graph_line_width() -> f32 {
+    2.0
+}
+
+/// Where the samples of a graph come from.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum GraphSource {
+    /// Last line of the output of a shell command.
+    Command(String),
+    /// Number stored in a file.
+    File(String),
+    /// CPU usage percentage, read from the procfs mounted at the given directory.
+    Cpu(String),
+    /// Memory usage percentage, read from the procfs mounted at the given directory.
+    Memory(String),
+    /// Percentage of the CPUs busy according to the 1 minute load average, read from the procfs
+    /// mounted at the given directory.
+    LoadAverage(String),
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum GraphStyle {
+    #[default]
+    Line,
+    Area,
+    Bar,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiskConfig {
+    /// Mount points to show, one per line.
+    pub mount_points: Vec<String>,
+    /// Seconds between reads.
+    #[serde(default = "default_disk_interval")]
+    pub interval: u64,
+    /// Text to show for each mount point, where `{mount}`, `{used}`, `{free}`, `{total}` and
+    /// `{percent}` are replaced by the mount point, its usage and its used percentage.
+    #[serde(default = "default_disk_format")]
+    pub format: String,
+    /// Draw a usage bar under each mount point.
+    pub bar: Option<MeterConfig>,
+    /// Thresholds on the used percentage.
+    #[serde(default = "default_disk_thresholds")]
+    pub thresholds: Thresholds,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_disk_interval() -> u64 {
+    60
+}
+
+fn default_disk_format() -> String {
+    "{mount} {used} / {total}".to_string()
+}
+
+fn default_disk_thresholds() -> Thresholds {
+    Thresholds::new(80.0, 95.0)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemperatureConfig {
+    /// Labels of the sensors to show, one per line. A label matches the sensor label
+    /// (`temp*_label`) or the chip name of a hwmon device, or the type of a thermal zone.
+    pub sensors: Vec<String>,
+    /// Directory holding the `hwmon` and `thermal` device classes.
+    #[serde(default = "default_sys_class_root")]
+    pub root: String,
+    /// Seconds between reads.
+    #[serde(default = "default_sample_interval")]
+    pub interval: u64,
+    /// Text to show for each sensor, where `{label}` and `{temperature}` are replaced by the
+    /// sensor label and its temperature in degrees Celsius.
+    #[serde(default = "default_temperature_format")]
+    pub format: String,
+    /// Thresholds in degrees Celsius.
+    #[serde(default = "default_temperature_thresholds")]
+    pub thresholds: Thresholds,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_sys_class_root() -> String {
+    "/sys/class".to_string()
+}
+
+fn default_temperature_format() -> String {
+    "{label} {temperature}°C".to_string()
+}
+
+fn default_temperature_thresholds() -> Thresholds {
+    Thresholds::new(70.0, 90.0)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Name of the interface, like `eth0` or `wlan0`.
+    pub interface: String,
+    /// Directory where procfs is mounted.
+    #[serde(default = "default_proc_root")]
+    pub proc_root: String,
+    /// Directory holding the `net` device class.
+    #[serde(default = "default_sys_class_root")]
+    pub sys_root: String,
+    /// Seconds between samples.
+    #[serde(default = "default_sample_interval")]
+    pub interval: u64,
+    /// Text to show, where `{interface}`, `{state}`, `{address}`, `{rx}` and `{tx}` are
+    /// replaced by the interface name, its operational state, its IP addresses and the receive
+    /// and transmit rates.
+    #[serde(default = "default_network_format")]
+    pub format: String,
+    /// Draw the receive and transmit rates history below the text.
+    pub sparkline: Option<MeterConfig>,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_network_format() -> String {
+    "{interface} {state} {address}\n↓ {rx} ↑ {tx}".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WeatherConfig {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Forecast endpoint, it must answer the same way the Open-Meteo forecast API does.
+    #[serde(default = "default_weather_url")]
+    pub url: String,
+    /// Seconds between requests.
+    #[serde(default = "default_weather_interval")]
+    pub interval: u64,
+    /// Text to show next to the icon, where `{temperature}`, `{unit}` and `{condition}` are
+    /// replaced by the current temperature, its unit and a description of the weather.
+    #[serde(default = "default_weather_format")]
+    pub format: String,
+    /// File where the last response is kept, so something can be shown before the first request
+    /// completes. Defaults to a file in the user cache directory.
+    pub cache: Option<String>,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_weather_url() -> String {
+    "https://api.open-meteo.com/v1/forecast".to_string()
+}
+
+fn default_weather_interval() -> u64 {
+    900
+}
+
+fn default_weather_format() -> String {
+    "{temperature}{unit} {condition}".to_string()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HttpConfig {
+    pub url: String,
+    /// Seconds between requests.
+    #[serde(default = "default_http_interval")]
+    pub interval: u64,
+    /// Request headers, `${NAME}` in a value is replaced by the `NAME` environment variable so
+    /// secrets don't have to live in the configuration file.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Values extracted from the JSON response, by name. Selectors look like
+    /// `$.builds[0].status`, negative indices count from the end of arrays.
+    pub values: BTreeMap<String, String>,
+    /// Text to show, where `{name}` is replaced by the value with that name.
+    pub template: String,
+    /// Seconds without a successful response after which the data is shown as stale, defaults
+    /// to three intervals.
+    pub stale_after: Option<u64>,
+    /// Appended to the text while the data is stale.
+    #[serde(default = "default_stale_indicator")]
+    pub stale_indicator: String,
+    /// Text color while the data is stale.
+    #[serde(default = "default_stale_color")]
+    pub stale_color: (u8, u8, u8, u8),
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_http_interval() -> u64 {
+    60
+}
+
+fn default_stale_indicator() -> String {
+    " (stale)".to_string()
+}
+
+fn default_stale_color() -> (u8, u8, u8, u8) {
+    (150, 150, 150, 100)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgendaConfig {
+    /// iCalendar files to read events from, they are read again whenever they change.
+    pub files: Vec<String>,
+    /// How many events to show at most.
+    #[serde(default = "default_agenda_events")]
+    pub events: usize,
+    /// Also show tomorrow's events, not only today's.
+    #[serde(default = "default_true")]
+    pub include_tomorrow: bool,
+    /// chrono format used for the start and end times.
+    #[serde(default = "default_agenda_time_format")]
+    pub time_format: String,
+    /// Text shown when there are no events left.
+    #[serde(default = "default_agenda_empty_text")]
+    pub empty_text: String,
+    /// Text color of the events happening right now.
+    #[serde(default = "default_highlight_color")]
+    pub highlight_color: (u8, u8, u8, u8),
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_agenda_events() -> usize {
+    5
+}
+
+fn default_agenda_time_format() -> String {
+    "%H:%M".to_string()
+}
+
+fn default_agenda_empty_text() -> String {
+    "No more events today".to_string()
+}
+
+fn default_highlight_color() -> (u8, u8, u8, u8) {
+    (52, 152, 219, 255)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarConfig {
+    #[serde(default)]
+    pub week_start: WeekStart,
+    /// Show ISO week numbers before each week.
+    #[serde(default)]
+    pub week_numbers: bool,
+    /// iCalendar files, days with events are marked in the grid.
+    #[serde(default)]
+    pub files: Vec<String>,
+    /// Background color behind today's date.
+    #[serde(default = "default_highlight_color")]
+    pub today_color: (u8, u8, u8, u8),
+    /// Color of the marker under days with events.
+    #[serde(default = "default_event_marker_color")]
+    pub event_color: (u8, u8, u8, u8),
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_event_marker_color() -> (u8, u8, u8, u8) {
+    (241, 196, 15, 255)
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NowPlayingConfig {
+    /// MPRIS player to follow, such as `spotify` or `vlc`. When unset the first player that is
+    /// playing is shown.
+    pub player: Option<String>,
+    /// Address of the bus to look for players on, defaults to the session bus.
+    pub bus_address: Option<String>,
+    /// Text next to the album art, where `{title}`, `{artist}`, `{album}`, `{status}`,
+    /// `{position}` and `{length}` are replaced by the current track details.
+    #[serde(default = "default_now_playing_format")]
+    pub format: String,
+    /// Size of the album art, in pixels. Set it to 0 to hide the art.
+    #[serde(default = "default_art_size")]
+    pub art_size: u32,
+    /// Progress bar drawn under the text, hidden when not set.
+    pub progress: Option<MeterConfig>,
+    #[serde(default)]
+    pub position: Position,
+    #[serde(flatten)]
+    pub font: Font,
+}
+
+fn default_now_playing_format() -> String {
+    "{title}\n{artist} - {album}".to_string()
+}
+
+fn default_art_size() -> u32 {
+    96
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImageConfig {
+    /// PNG, JPEG, GIF, WebP or SVG file to show, animated GIF, PNG and WebP files are played.
+    pub path: String,
+    /// Width to draw the image at, in pixels. When only one of `width` and `height` is set the
+    /// other one follows the aspect ratio of the image, when both are set the image is fit inside.
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    /// Factor the image size is multiplied by, applied after `width` and `height`.
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// From 0, fully transparent, to 1, fully opaque.
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    #[serde(default)]
+    pub corner_radius: f32,
+    #[serde(flatten)]
+    pub animation: Animation,
+    #[serde(default)]
+    pub position: Position,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_opacity() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RectangleConfig {
+    pub width: f32,
+    pub height: f32,
+    #[serde(default)]
+    pub corner_radius: f32,
+    pub fill: Option<Fill>,
+    pub stroke: Option<StrokeConfig>,
+    #[serde(default)]
+    pub position: Position,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CircleConfig {
+    pub radius: f32,
+    pub fill: Option<Fill>,
+    pub stroke: Option<StrokeConfig>,
+    #[serde(default)]
+    pub position: Position,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LineConfig {
+    pub length: f32,
+    /// Direction of the line in degrees clockwise, 0 being horizontal.
+    #[serde(default)]
+    pub angle: f32,
+    pub stroke: StrokeConfig,
+    #[serde(default)]
+    pub position: Position,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PanelConfig {
+    /// Size of the panel when it has no child.
+    #[serde(default)]
+    pub width: f32,
+    #[serde(default)]
+    pub height: f32,
+    /// Space between the edges of the panel and its child.
+    #[serde(default)]
+    pub padding: f32,
+    #[serde(default)]
+    pub corner_radius: f32,
+    pub fill: Option<Fill>,
+    pub stroke: Option<StrokeConfig>,
+    /// Blurs what is under the panel, again only when the background changes or the panel moves.
+    pub backdrop: Option<Backdrop>,
+    /// Widget drawn on top of the panel. The panel wraps it, so its own size is only used without
+    /// a child, and the child's position is ignored.
+    pub child: Option<Box<Widget>>,
+    #[serde(default)]
+    pub position: Position,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContainerConfig {
+    /// Widgets laid out one after the other, their own positions are ignored.
+    pub children: Vec<Widget>,
+    /// Space between two children, in pixels.
+    #[serde(default)]
+    pub spacing: f32,
+    /// How children are lined up across the container, e.g. `Start` puts the children of a row
+    /// against its top.
+    #[serde(default)]
+    pub align: Align,
+    #[serde(default)]
+    pub position: Position,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StackConfig {
+    /// Widgets drawn on top of each other, the last one on top. Their own positions are ignored.
+    pub children: Vec<Widget>,
+    /// Point of the stack children smaller than it are pinned to.
+    #[serde(default)]
+    pub align: Anchor,
+    #[serde(default)]
+    pub position: Position,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GridConfig {
+    /// Widgets placed row by row, their own positions are ignored.
+    pub children: Vec<Widget>,
+    #[serde(default = "default_grid_columns")]
+    pub columns: usize,
+    #[serde(default)]
+    pub column_spacing: f32,
+    #[serde(default)]
+    pub row_spacing: f32,
+    /// Point of its cell a child smaller than the cell is pinned to.
+    #[serde(default)]
+    pub align: Anchor,
+    #[serde(default)]
+    pub position: Position,
+}
+
+fn default_grid_columns() -> usize {
+    2
+}
@@ -0,0 +1,255 @@
+use tiny_skia::Pixmap;
+
+/// Approximates a Gaussian blur with standard deviation `sigma`, in pixels, by three box blurs.
+///
+/// Pixels past the edges are taken to be the same as the closest edge pixel.
+pub fn blur(pixmap: &mut Pixmap, sigma: f32) {
+    if sigma <= 0.0 {
+        return;
+    }
+
+    let width = pixmap.width() as usize;
+    let height = pixmap.height() as usize;
+    let mut scratch = vec![0; pixmap.data().len()];
+
+    for radius in box_radii(sigma) {
+        let horizontal = Pass {
+            length: width,
+            lines: height,
+            step: 1,
+            line_step: width,
+            radius,
+        };
+        let vertical = Pass {
+            length: height,
+            lines: width,
+            step: width,
+            line_step: 1,
+            radius,
+        };
+
+        horizontal.run(pixmap.data(), &mut scratch);
+        vertical.run(&scratch, pixmap.data_mut());
+    }
+}
+
+/// Multiplies the color of every pixel by `factor`, darkening the image below 1.
+pub fn brightness(pixmap: &mut Pixmap, factor: f32) {
+    let factor = factor.max(0.0);
+
+    for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32;
+
+        // Colors are premultiplied, so they can't go past the alpha.
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as f32 * factor).round().min(alpha) as u8;
+        }
+    }
+}
+
+/// Moves every color towards its gray level by `factor`, 0 gives a grayscale image and values
+/// above 1 make colors more vivid.
+pub fn saturation(pixmap: &mut Pixmap, factor: f32) {
+    let factor = factor.max(0.0);
+
+    for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32;
+        let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(f32::from);
+        let luma = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+
+        for channel in &mut pixel[..3] {
+            let saturated = luma + (*channel as f32 - luma) * factor;
+            *channel = saturated.round().clamp(0.0, alpha) as u8;
+        }
+    }
+}
+
+/// Draws `color` over every pixel, keeping the transparency of the image.
+pub fn tint(pixmap: &mut Pixmap, (r, g, b, a): (u8, u8, u8, u8)) {
+    let amount = a as f32 / 255.0;
+
+    for pixel in pixmap.data_mut().chunks_exact_mut(4) {
+        let alpha = pixel[3] as f32 / 255.0;
+
+        for (channel, tint) in pixel[..3].iter_mut().zip([r, g, b]) {
+            let tinted = *channel as f32 * (1.0 - amount) + tint as f32 * alpha * amount;
+            *channel = tinted.round() as u8;
+        }
+    }
+}
+
+/// Radii of the three box blurs closest to a Gaussian blur, see
+/// http://blog.ivank.net/fastest-gaussian-blur.html.
+fn box_radii(sigma: f32) -> [usize; 3] {
+    const PASSES: f32 = 3.0;
+
+    let ideal = (12.0 * sigma * sigma / PASSES + 1.0).sqrt();
+    let mut lower = ideal.floor() as i32;
+    if lower % 2 == 0 {
+        lower -= 1;
+    }
+    let upper = lower + 2;
+
+    let lower_f = lower as f32;
+    let lower_count = ((12.0 * sigma * sigma
+        - PASSES * lower_f * lower_f
+        - 4.0 * PASSES * lower_f
+        - 3.0 * PASSES)
+        / (-4.0 * lower_f - 4.0))
+        .round() as i32;
+
+    let mut radii = [0; 3];
+    for (pass, radius) in radii.iter_mut().enumerate() {
+        let size = match (pass as i32) < lower_count {
+            true => lower,
+            false => upper,
+        };
+        *radius = ((size - 1) / 2).max(0) as usize;
+    }
+
+    radii
+}
+
+/// A box blur along the rows or the columns of an image, in pixels.
+struct Pass {
+    /// Pixels in a line.
+    length: usize,
+    lines: usize,
+    /// Distance between two pixels of a line.
+    step: usize,
+    /// Distance between the first pixels of two lines.
+    line_step: usize,
+    radius: usize,
+}
+
+impl Pass {
+    fn run(&self, source: &[u8], target: &mut [u8]) {
+        if self.length == 0 {
+            return;
+        }
+
+        let window = (self.radius * 2 + 1) as u32;
+        let last = self.length as isize - 1;
+
+        for line in 0..self.lines {
+            let offset = |index: isize| {
+                (line * self.line_step + index.clamp(0, last) as usize * self.step) * 4
+            };
+
+            let mut sums = [0u32; 4];
+            for index in -(self.radius as isize)..=self.radius as isize {
+                let at = offset(index);
+                for (sum, value) in sums.iter_mut().zip(&source[at..at + 4]) {
+                    *sum += *value as u32;
+                }
+            }
+
+            for index in 0..self.length as isize {
+                let at = offset(index);
+                for (value, sum) in target[at..at + 4].iter_mut().zip(sums) {
+                    *value = ((sum + window / 2) / window) as u8;
+                }
+
+                let added = offset(index + self.radius as isize + 1);
+                let removed = offset(index - self.radius as isize);
+                for channel in 0..4 {
+                    sums[channel] += source[added + channel] as u32;
+                    sums[channel] -= source[removed + channel] as u32;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tiny_skia::ColorU8;
+
+    use super::*;
+
+    /// A single row of pixels with the given colors, premultiplied.
+    fn pixmap(pixels: &[[u8; 4]]) -> Pixmap {
+        let mut pixmap = Pixmap::new(pixels.len() as u32, 1).unwrap();
+        for (pixel, &[r, g, b, a]) in pixmap.pixels_mut().iter_mut().zip(pixels) {
+            *pixel = ColorU8::from_rgba(r, g, b, a).premultiply();
+        }
+        pixmap
+    }
+
+    fn pixels(pixmap: &Pixmap) -> Vec<[u8; 4]> {
+        pixmap
+            .data()
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect()
+    }
+
+    #[test]
+    fn blur_spreads_a_pixel_and_keeps_flat_areas() {
+        let mut flat = pixmap(&[[40, 80, 120, 255]; 5]);
+        blur(&mut flat, 2.0);
+        assert_eq!(pixels(&flat), [[40, 80, 120, 255]; 5]);
+
+        let mut spot = pixmap(&[
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+            [255, 255, 255, 255],
+            [0, 0, 0, 0],
+            [0, 0, 0, 0],
+        ]);
+        blur(&mut spot, 1.0);
+        let spread = pixels(&spot);
+        assert!(
+            spread[2][3] < 255 && spread[1][3] > 0 && spread[3][3] > 0,
+            "{spread:?}"
+        );
+        assert_eq!(spread[1], spread[3]);
+        // Premultiplied colors never go past their alpha.
+        assert!(spread
+            .iter()
+            .all(|pixel| pixel[..3].iter().all(|&c| c <= pixel[3])));
+
+        let mut untouched = pixmap(&[[1, 2, 3, 255], [4, 5, 6, 255]]);
+        blur(&mut untouched, 0.0);
+        assert_eq!(pixels(&untouched), [[1, 2, 3, 255], [4, 5, 6, 255]]);
+    }
+
+    #[test]
+    fn brightness_scales_colors_up_to_their_alpha() {
+        let mut pixmap = pixmap(&[[100, 50, 200, 255], [255, 0, 0, 128]]);
+
+        brightness(&mut pixmap, 0.5);
+        assert_eq!(pixels(&pixmap), [[50, 25, 100, 255], [64, 0, 0, 128]]);
+
+        brightness(&mut pixmap, 4.0);
+        assert_eq!(pixels(&pixmap), [[200, 100, 255, 255], [128, 0, 0, 128]]);
+    }
+
+    #[test]
+    fn saturation_moves_colors_towards_gray() {
+        let mut gray = pixmap(&[[255, 0, 0, 255], [0, 0, 0, 0]]);
+        saturation(&mut gray, 0.0);
+        assert_eq!(pixels(&gray), [[54, 54, 54, 255], [0, 0, 0, 0]]);
+
+        let mut vivid = pixmap(&[[200, 100, 100, 255]]);
+        saturation(&mut vivid, 2.0);
+        let [r, g, b, _] = pixels(&vivid)[0];
+        assert!(r > 200 && g < 100 && g == b, "{r} {g} {b}");
+
+        let mut half = pixmap(&[[255, 0, 0, 128]]);
+        saturation(&mut half, 1.0);
+        assert_eq!(pixels(&half), [[128, 0, 0, 128]]);
+    }
+
+    #[test]
+    fn tint_is_drawn_over_with_the_image_transparency() {
+        let mut pixmap = pixmap(&[[0, 0, 0, 255], [0, 0, 0, 0], [200, 200, 200, 255]]);
+
+        tint(&mut pixmap, (0, 0, 255, 128));
+
+        assert_eq!(
+            pixels(&pixmap),
+            [[0, 0, 128, 255], [0, 0, 0, 0], [100, 100, 228, 255]]
+        );
+    }
+}
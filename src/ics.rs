@@ -0,0 +1,514 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::TimeZone;
+use chrono::Utc;
+use rrule::RRule;
+use rrule::RRuleSet;
+use rrule::Tz;
+use rrule::Unvalidated;
+
+/// Upper bound of occurrences expanded for a single recurring event in one query.
+const MAX_OCCURRENCES: u16 = 500;
+
+/// A single occurrence of an event.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub summary: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub all_day: bool,
+}
+
+impl Event {
+    pub fn is_ongoing(&self, now: DateTime<Local>) -> bool {
+        self.start <= now && now < self.end
+    }
+}
+
+/// A `VEVENT` as written in the file, possibly recurring.
+#[derive(Debug, Clone)]
+struct EventDefinition {
+    uid: Option<String>,
+    summary: String,
+    start: DateTime<Tz>,
+    duration: Duration,
+    all_day: bool,
+    recurrence: Option<RRuleSet>,
+    /// Set on events that replace a single occurrence of a recurring event.
+    recurrence_id: Option<DateTime<Utc>>,
+}
+
+/// Events read from one or more iCalendar files.
+#[derive(Debug, Clone, Default)]
+pub struct Calendar {
+    events: Vec<EventDefinition>,
+}
+
+impl Calendar {
+    /// Reads every file, skipping the ones that can't be read.
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> Self {
+        let events = paths
+            .iter()
+            .filter_map(|path| std::fs::read_to_string(path).ok())
+            .flat_map(|contents| parse_events(&contents))
+            .collect();
+
+        Self { events }
+    }
+
+    /// Occurrences overlapping `from..to`, sorted by start.
+    pub fn events_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Vec<Event> {
+        // Occurrences replaced by a modified instance are skipped in their series.
+        let overrides: HashSet<(&str, DateTime<Utc>)> = self
+            .events
+            .iter()
+            .filter_map(|event| Some((event.uid.as_deref()?, event.recurrence_id?)))
+            .collect();
+
+        let mut events: Vec<Event> = self
+            .events
+            .iter()
+            .flat_map(|definition| {
+                let starts = match &definition.recurrence {
+                    Some(recurrence) => recurrence
+                        .clone()
+                        .after((from - definition.duration).with_timezone(&Tz::LOCAL))
+                        .before(to.with_timezone(&Tz::LOCAL))
+                        .all(MAX_OCCURRENCES)
+                        .dates
+                        .into_iter()
+                        .filter(|start| match definition.uid.as_deref() {
+                            Some(uid) => !overrides.contains(&(uid, start.with_timezone(&Utc))),
+                            None => true,
+                        })
+                        .collect(),
+                    None => vec![definition.start],
+                };
+
+                starts.into_iter().map(|start| {
+                    let start = start.with_timezone(&Local);
+
+                    Event {
+                        summary: definition.summary.clone(),
+                        start,
+                        end: start + definition.duration,
+                        all_day: definition.all_day,
+                    }
+                })
+            })
+            .filter(|event| event.start < to && (event.end > from || event.start >= from))
+            .collect();
+
+        events.sort_by_key(|event| event.start);
+        events
+    }
+}
+
+/// A content line, like `DTSTART;TZID=Europe/Berlin:20240101T090000`.
+struct Property<'a> {
+    name: String,
+    params: Vec<(String, &'a str)>,
+    value: &'a str,
+}
+
+impl Property<'_> {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.trim_matches('"'))
+    }
+}
+
+fn parse_property(line: &str) -> Option<Property<'_>> {
+    // The value starts at the first colon outside of a quoted parameter value.
+    let mut quoted = false;
+    let split = line.char_indices().find_map(|(index, char)| match char {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+
+    let (head, value) = (&line[..split], &line[split + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(name, value)| (name.to_ascii_uppercase(), value))
+        .collect();
+
+    Some(Property {
+        name,
+        params,
+        value,
+    })
+}
+
+/// Joins folded lines, continuation lines start with a space or a tab.
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim_end_matches('\r');
+
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+fn parse_events(contents: &str) -> Vec<EventDefinition> {
+    let lines = unfold(contents);
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in &lines {
+        let Some(property) = parse_property(line) else {
+            continue;
+        };
+
+        match (property.name.as_str(), property.value) {
+            ("BEGIN", "VEVENT") => current = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(event) = current
+                    .take()
+                    .and_then(|properties| build_event(&properties))
+                {
+                    events.push(event);
+                }
+            }
+            _ => {
+                if let Some(properties) = current.as_mut() {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    events
+}
+
+fn build_event(properties: &[Property]) -> Option<EventDefinition> {
+    let find = |name: &str| properties.iter().find(|property| property.name == name);
+
+    let start_property = find("DTSTART")?;
+    let (start, all_day) = parse_date_time(start_property.value, start_property)?;
+
+    let duration = match (find("DTEND"), find("DURATION")) {
+        (Some(end), _) => parse_date_time(end.value, end).map(|(end, _)| end - start),
+        (None, Some(duration)) => parse_duration(duration.value),
+        (None, None) => None,
+    }
+    .unwrap_or_else(|| match all_day {
+        true => Duration::days(1),
+        false => Duration::zero(),
+    });
+
+    let rule = find("RRULE").and_then(|rule| {
+        let validated = rule
+            .value
+            .parse::<RRule<Unvalidated>>()
+            .and_then(|rule| rule.validate(start));
+
+        // The event is still shown once rather than dropped when the rule can't be used.
+        validated
+            .inspect_err(|error| eprintln!("ignoring recurrence rule {:?}: {error}", rule.value))
+            .ok()
+    });
+
+    let recurrence = match rule {
+        Some(rule) => {
+            let mut recurrence = RRuleSet::new(start).rrule(rule);
+
+            for property in properties {
+                let dates = property
+                    .value
+                    .split(',')
+                    .filter_map(|value| parse_date_time(value, property))
+                    .map(|(date, _)| date);
+
+                match property.name.as_str() {
+                    "EXDATE" => recurrence = dates.fold(recurrence, RRuleSet::exdate),
+                    "RDATE" => recurrence = dates.fold(recurrence, RRuleSet::rdate),
+                    _ => {}
+                }
+            }
+
+            Some(recurrence)
+        }
+        None => None,
+    };
+
+    let recurrence_id = find("RECURRENCE-ID")
+        .and_then(|property| parse_date_time(property.value, property))
+        .map(|(date, _)| date.with_timezone(&Utc));
+
+    Some(EventDefinition {
+        uid: find("UID").map(|property| property.value.to_string()),
+        summary: find("SUMMARY")
+            .map(|property| unescape(property.value))
+            .unwrap_or_default(),
+        start,
+        duration,
+        all_day,
+        recurrence,
+        recurrence_id,
+    })
+}
+
+/// Parses a `DATE` or `DATE-TIME` value, returning whether it was a whole day.
+///
+/// UTC times end with `Z`, times with a `TZID` parameter are in that IANA time zone and any
+/// other time (or a time zone chrono-tz doesn't know) is taken as local time.
+fn parse_date_time(value: &str, property: &Property) -> Option<(DateTime<Tz>, bool)> {
+    let value = value.trim();
+
+    if property.param("VALUE") == Some("DATE") || value.len() == 8 {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        let start = Tz::LOCAL
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()?;
+
+        return Some((start, true));
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let date_time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Tz::UTC.from_utc_datetime(&date_time), false));
+    }
+
+    let date_time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let tz = property
+        .param("TZID")
+        .and_then(|tzid| tzid.parse::<chrono_tz::Tz>().ok())
+        .map(Tz::Tz)
+        .unwrap_or(Tz::LOCAL);
+
+    Some((tz.from_local_datetime(&date_time).earliest()?, false))
+}
+
+/// Parses durations like `PT1H30M`, `P1D` or `P2W`.
+fn parse_duration(value: &str) -> Option<Duration> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+
+    for char in value.strip_prefix('P')?.chars() {
+        match char {
+            '0'..='9' => number.push(char),
+            'T' => {}
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+
+                duration += match unit {
+                    'W' => Duration::weeks(amount),
+                    'D' => Duration::days(amount),
+                    'H' => Duration::hours(amount),
+                    'M' => Duration::minutes(amount),
+                    'S' => Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    Some(duration * sign)
+}
+
+/// Resolves the backslash escapes of a `TEXT` value, newlines become spaces.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(char) = chars.next() {
+        match (char, chars.clone().next()) {
+            ('\\', Some('n' | 'N')) => {
+                chars.next();
+                unescaped.push(' ');
+            }
+            ('\\', Some(escaped @ ('\\' | ',' | ';'))) => {
+                chars.next();
+                unescaped.push(escaped);
+            }
+            (char, _) => unescaped.push(char),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn property(line: &str) -> Property<'_> {
+        parse_property(line).unwrap()
+    }
+
+    fn utc(value: &str) -> DateTime<Local> {
+        NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+            .unwrap()
+            .and_utc()
+            .with_timezone(&Local)
+    }
+
+    fn calendar(events: &str) -> Calendar {
+        Calendar {
+            events: parse_events(&format!("BEGIN:VCALENDAR\r\n{events}END:VCALENDAR\r\n")),
+        }
+    }
+
+    #[test]
+    fn unfold_joins_continuation_lines() {
+        let lines = unfold("SUMMARY:Long\r\n  meeting\r\n\tname\r\nUID:1\r\n");
+
+        assert_eq!(lines, ["SUMMARY:Long meetingname", "UID:1"]);
+    }
+
+    #[test]
+    fn parse_property_splits_name_params_and_value() {
+        let line = "dtstart;TZID=\"Europe/Berlin\";X-NOTE=\"a:b\":20240101T090000";
+        let parsed = property(line);
+
+        assert_eq!(parsed.name, "DTSTART");
+        assert_eq!(parsed.param("TZID"), Some("Europe/Berlin"));
+        assert_eq!(parsed.param("X-NOTE"), Some("a:b"));
+        assert_eq!(parsed.value, "20240101T090000");
+        assert!(parse_property("no colon here").is_none());
+    }
+
+    #[test]
+    fn unescape_resolves_escapes() {
+        assert_eq!(
+            unescape(r"Lunch\, then coffee\; maybe\nor not \\o/"),
+            r"Lunch, then coffee; maybe or not \o/"
+        );
+    }
+
+    #[test]
+    fn parse_duration_reads_every_unit() {
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("P1D"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("P2W"), Some(Duration::weeks(2)));
+        assert_eq!(
+            parse_duration("-P1DT2S"),
+            Some(-(Duration::days(1) + Duration::seconds(2)))
+        );
+        assert_eq!(parse_duration("1H"), None);
+        assert_eq!(parse_duration("PT1X"), None);
+    }
+
+    #[test]
+    fn parse_date_time_handles_utc_tzid_and_dates() {
+        let line = property("DTSTART:20240101T090000Z");
+        let (start, all_day) = parse_date_time(line.value, &line).unwrap();
+        assert_eq!(start.with_timezone(&Local), utc("20240101T090000"));
+        assert!(!all_day);
+
+        // 09:00 in Berlin is 08:00 UTC in winter.
+        let line = property("DTSTART;TZID=Europe/Berlin:20240101T090000");
+        let (start, all_day) = parse_date_time(line.value, &line).unwrap();
+        assert_eq!(start.with_timezone(&Local), utc("20240101T080000"));
+        assert!(!all_day);
+
+        let line = property("DTSTART;VALUE=DATE:20240101");
+        let (start, all_day) = parse_date_time(line.value, &line).unwrap();
+        assert_eq!(
+            start.with_timezone(&Local).naive_local(),
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        );
+        assert!(all_day);
+
+        let line = property("DTSTART:yesterday");
+        assert!(parse_date_time(line.value, &line).is_none());
+    }
+
+    #[test]
+    fn all_day_events_last_a_day() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\n\
+             SUMMARY:Holiday\\, finally\r\n\
+             DTSTART;VALUE=DATE:20240101\r\n\
+             END:VEVENT\r\n",
+        );
+
+        let events = calendar.events_between(utc("20231225T000000"), utc("20240110T000000"));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].summary, "Holiday, finally");
+        assert!(events[0].all_day);
+        assert_eq!(events[0].end - events[0].start, Duration::days(1));
+    }
+
+    #[test]
+    fn recurring_events_skip_excluded_and_overridden_occurrences() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:standup\r\n\
+             SUMMARY:Standup\r\n\
+             DTSTART:20240101T090000Z\r\n\
+             DURATION:PT15M\r\n\
+             RRULE:FREQ=DAILY;COUNT=4\r\n\
+             EXDATE:20240102T090000Z\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:standup\r\n\
+             SUMMARY:Late standup\r\n\
+             RECURRENCE-ID:20240103T090000Z\r\n\
+             DTSTART:20240103T110000Z\r\n\
+             DURATION:PT15M\r\n\
+             END:VEVENT\r\n",
+        );
+
+        let events = calendar.events_between(utc("20240101T000000"), utc("20240110T000000"));
+        let summary: Vec<_> = events
+            .iter()
+            .map(|event| (event.summary.as_str(), event.start))
+            .collect();
+
+        assert_eq!(
+            summary,
+            [
+                ("Standup", utc("20240101T090000")),
+                ("Late standup", utc("20240103T110000")),
+                ("Standup", utc("20240104T090000")),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_recurrence_rule_keeps_the_first_occurrence() {
+        let calendar = calendar(
+            "BEGIN:VEVENT\r\n\
+             SUMMARY:Broken\r\n\
+             DTSTART:20240101T090000Z\r\n\
+             RRULE:FREQ=SOMETIMES\r\n\
+             END:VEVENT\r\n",
+        );
+
+        let events = calendar.events_between(utc("20240101T000000"), utc("20240110T000000"));
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].start, utc("20240101T090000"));
+    }
+}
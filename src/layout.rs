@@ -0,0 +1,513 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::Serialize;
+use serde::Serializer;
+use thiserror::Error;
+use winit::dpi::PhysicalSize;
+
+/// A distance along one of the window axes.
+///
+/// Written as a number of pixels, or as a string with one of the `px`, `%`, `vw` and `vh` units,
+/// e.g. `12`, `"-8px"`, `"5%"` or `"2.5vh"`. Percentages are relative to the window size along the
+/// axis the length is used for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Px(f32),
+    Percent(f32),
+    /// Percentage of the window width.
+    Vw(f32),
+    /// Percentage of the window height.
+    Vh(f32),
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Length::Px(0.0)
+    }
+}
+
+impl Length {
+    /// Converts the length to pixels, `axis` being the size of the window along the axis it is
+    /// used for.
+    pub fn to_pixels(self, axis: f32, window: PhysicalSize<u32>) -> f32 {
+        match self {
+            Length::Px(pixels) => pixels,
+            Length::Percent(percent) => axis * percent / 100.0,
+            Length::Vw(percent) => window.width as f32 * percent / 100.0,
+            Length::Vh(percent) => window.height as f32 * percent / 100.0,
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = LengthError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        let (number, unit): (_, fn(f32) -> Length) = if let Some(number) = text.strip_suffix('%') {
+            (number, Length::Percent)
+        } else if let Some(number) = text.strip_suffix("vw") {
+            (number, Length::Vw)
+        } else if let Some(number) = text.strip_suffix("vh") {
+            (number, Length::Vh)
+        } else if let Some(number) = text.strip_suffix("px") {
+            (number, Length::Px)
+        } else {
+            (text, Length::Px)
+        };
+
+        number
+            .trim()
+            .parse()
+            .map(unit)
+            .map_err(|_| LengthError(text.to_string()))
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Px(pixels) => write!(f, "{pixels}px"),
+            Length::Percent(percent) => write!(f, "{percent}%"),
+            Length::Vw(percent) => write!(f, "{percent}vw"),
+            Length::Vh(percent) => write!(f, "{percent}vh"),
+        }
+    }
+}
+
+impl Serialize for Length {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Length::Px(pixels) => serializer.serialize_f32(*pixels),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Pixels(f32),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Pixels(pixels) => Ok(Length::Px(pixels)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("invalid length {0:?}, expected a number optionally followed by px, %, vw or vh")]
+pub struct LengthError(String);
+
+/// One of the nine points of a box a widget can be pinned by, the same point of the window and of
+/// the widget are lined up.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    #[default]
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Where the anchor is along each axis, from 0 at the left or top to 1 at the right or bottom.
+    pub fn fractions(self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+/// How a box smaller than the space it is given is lined up along one axis.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Align {
+    /// Left or top.
+    Start,
+    #[default]
+    Center,
+    /// Right or bottom.
+    End,
+}
+
+impl Align {
+    /// Where the box goes along the axis, from 0 at the start to 1 at the end.
+    pub fn fraction(self) -> f32 {
+        match self {
+            Align::Start => 0.0,
+            Align::Center => 0.5,
+            Align::End => 1.0,
+        }
+    }
+}
+
+/// Which side of another widget a widget is placed on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Side {
+    Above,
+    #[default]
+    Below,
+    LeftOf,
+    RightOf,
+}
+
+/// Space kept free along the edges of the window, given either as one length for every side or as
+/// a table with `top`, `right`, `bottom` and `left`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Margin {
+    pub top: Length,
+    pub right: Length,
+    pub bottom: Length,
+    pub left: Length,
+}
+
+impl<'de> Deserialize<'de> for Margin {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Sides {
+            #[serde(default)]
+            top: Length,
+            #[serde(default)]
+            right: Length,
+            #[serde(default)]
+            bottom: Length,
+            #[serde(default)]
+            left: Length,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            All(Length),
+            Sides(Sides),
+        }
+
+        Ok(match Raw::deserialize(deserializer)? {
+            Raw::All(length) => Margin {
+                top: length,
+                right: length,
+                bottom: length,
+                left: length,
+            },
+            Raw::Sides(sides) => Margin {
+                top: sides.top,
+                right: sides.right,
+                bottom: sides.bottom,
+                left: sides.left,
+            },
+        })
+    }
+}
+
+/// Where a box of the given size goes inside the window.
+pub struct Placement {
+    pub anchor: Anchor,
+    pub margin: Margin,
+    /// Moves the box after it has been anchored, positive values go right and down.
+    pub offset: (Length, Length),
+}
+
+impl Placement {
+    /// Computes the top-left corner of a box of `size` inside `window`, which can be outside of
+    /// the window when the box doesn't fit or is moved out of it.
+    pub fn resolve(&self, window: PhysicalSize<u32>, (width, height): (f32, f32)) -> (f32, f32) {
+        let window_width = window.width as f32;
+        let window_height = window.height as f32;
+
+        let left = self.margin.left.to_pixels(window_width, window);
+        let right = self.margin.right.to_pixels(window_width, window);
+        let top = self.margin.top.to_pixels(window_height, window);
+        let bottom = self.margin.bottom.to_pixels(window_height, window);
+
+        let (anchor_x, anchor_y) = self.anchor.fractions();
+
+        // The box is anchored inside the area the margins leave free.
+        let x = left + anchor_x * (window_width - left - right - width);
+        let y = top + anchor_y * (window_height - top - bottom - height);
+
+        (
+            x + self.offset.0.to_pixels(window_width, window),
+            y + self.offset.1.to_pixels(window_height, window),
+        )
+    }
+
+    /// Largest size a box can have and still fit between the margins once anchored and moved,
+    /// which is zero along an axis the margins leave no room on.
+    pub fn space(&self, window: PhysicalSize<u32>) -> (f32, f32) {
+        let window_width = window.width as f32;
+        let window_height = window.height as f32;
+
+        let free_width = window_width
+            - self.margin.left.to_pixels(window_width, window)
+            - self.margin.right.to_pixels(window_width, window);
+        let free_height = window_height
+            - self.margin.top.to_pixels(window_height, window)
+            - self.margin.bottom.to_pixels(window_height, window);
+
+        let (anchor_x, anchor_y) = self.anchor.fractions();
+
+        (
+            axis_space(
+                free_width,
+                anchor_x,
+                self.offset.0.to_pixels(window_width, window),
+            ),
+            axis_space(
+                free_height,
+                anchor_y,
+                self.offset.1.to_pixels(window_height, window),
+            ),
+        )
+    }
+}
+
+/// Largest length of a box anchored at `anchor` along an axis with `free` pixels between the
+/// margins, then moved by `offset`. The box grows away from its anchor, so moving it takes room
+/// from the side it is moved to and gives some on the other.
+fn axis_space(free: f32, anchor: f32, offset: f32) -> f32 {
+    let mut space = free;
+
+    if anchor > 0.0 {
+        space = space.min(free + offset / anchor);
+    }
+    if anchor < 1.0 {
+        space = space.min(free - offset / (1.0 - anchor));
+    }
+
+    space.max(0.0)
+}
+
+#[derive(Debug, Error)]
+pub enum LayoutError {
+    #[error("no widget has the id {0:?}")]
+    UnknownWidget(String),
+
+    #[error("more than one widget has the id {0:?}")]
+    DuplicateId(String),
+
+    #[error("widgets are placed relative to each other in a loop: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+
+    #[error("widgets inside a panel or container can't be placed relative to {0:?}")]
+    NestedRelation(String),
+}
+
+/// Orders widgets so every widget comes after the one it is placed relative to, keeping the
+/// original order otherwise.
+///
+/// `ids` holds the id of every widget and `targets` the id of the widget each one is placed
+/// relative to. Returns the new order as indices into both, along with the index of the target of
+/// each widget.
+pub fn dependency_order(
+    ids: &[Option<&str>],
+    targets: &[Option<&str>],
+) -> Result<(Vec<usize>, Vec<Option<usize>>), LayoutError> {
+    let mut indices = HashMap::new();
+    for (index, id) in ids.iter().enumerate() {
+        if let Some(id) = id {
+            if indices.insert(*id, index).is_some() {
+                return Err(LayoutError::DuplicateId(id.to_string()));
+            }
+        }
+    }
+
+    let targets = targets
+        .iter()
+        .map(|target| {
+            target
+                .map(|id| {
+                    indices
+                        .get(id)
+                        .copied()
+                        .ok_or_else(|| LayoutError::UnknownWidget(id.to_string()))
+                })
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        New,
+        Visiting,
+        Done,
+    }
+
+    let mut states = vec![State::New; ids.len()];
+    let mut order = Vec::with_capacity(ids.len());
+
+    for start in 0..ids.len() {
+        // Follows the chain of targets from `start`, widgets only depend on a single other one.
+        let mut chain = Vec::new();
+        let mut current = Some(start);
+
+        while let Some(index) = current.filter(|&index| states[index] != State::Done) {
+            if states[index] == State::Visiting {
+                let loop_start = chain.iter().position(|&other| other == index).unwrap_or(0);
+                let names = chain[loop_start..]
+                    .iter()
+                    .chain([&index])
+                    .map(|&index| ids[index].unwrap_or("?").to_string())
+                    .collect();
+
+                return Err(LayoutError::Cycle(names));
+            }
+
+            states[index] = State::Visiting;
+            chain.push(index);
+            current = targets[index];
+        }
+
+        for &index in chain.iter().rev() {
+            states[index] = State::Done;
+            order.push(index);
+        }
+    }
+
+    Ok((order, targets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placement(anchor: Anchor, margin: f32, offset: (f32, f32)) -> Placement {
+        let margin = Length::Px(margin);
+
+        Placement {
+            anchor,
+            margin: Margin {
+                top: margin,
+                right: margin,
+                bottom: margin,
+                left: margin,
+            },
+            offset: (Length::Px(offset.0), Length::Px(offset.1)),
+        }
+    }
+
+    #[test]
+    fn box_larger_than_the_window_goes_past_its_edges() {
+        let window = PhysicalSize::new(100, 50);
+        let centered = placement(Anchor::Center, 0.0, (0.0, 0.0));
+        let bottom_right = placement(Anchor::BottomRight, 10.0, (0.0, 0.0));
+
+        assert_eq!(centered.resolve(window, (300.0, 70.0)), (-100.0, -10.0));
+        assert_eq!(bottom_right.resolve(window, (300.0, 70.0)), (-210.0, -30.0));
+    }
+
+    #[test]
+    fn tiny_windows_leave_no_space() {
+        let centered = placement(Anchor::Center, 0.0, (0.0, 0.0));
+        let with_margin = placement(Anchor::TopLeft, 4.0, (0.0, 0.0));
+
+        assert_eq!(centered.space(PhysicalSize::new(1, 1)), (1.0, 1.0));
+        assert_eq!(
+            centered.resolve(PhysicalSize::new(1, 1), (1.0, 1.0)),
+            (0.0, 0.0)
+        );
+        assert_eq!(centered.space(PhysicalSize::new(0, 0)), (0.0, 0.0));
+        assert_eq!(
+            centered.resolve(PhysicalSize::new(0, 0), (10.0, 4.0)),
+            (-5.0, -2.0)
+        );
+        assert_eq!(with_margin.space(PhysicalSize::new(1, 1)), (0.0, 0.0));
+        assert_eq!(
+            with_margin.resolve(PhysicalSize::new(1, 1), (2.0, 2.0)),
+            (4.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn offsets_move_the_space_left() {
+        let window = PhysicalSize::new(100, 100);
+
+        // Moving a centered box takes twice the offset from the side it is moved to.
+        assert_eq!(
+            placement(Anchor::Center, 0.0, (10.0, -20.0)).space(window),
+            (80.0, 60.0)
+        );
+        // Moving a box out of the window never gives it more room than the margins leave.
+        assert_eq!(
+            placement(Anchor::TopLeft, 0.0, (-10.0, 30.0)).space(window),
+            (100.0, 70.0)
+        );
+        assert_eq!(
+            placement(Anchor::BottomRight, 0.0, (-10.0, 30.0)).space(window),
+            (90.0, 100.0)
+        );
+        // Moved past the opposite edge, nothing fits.
+        assert_eq!(
+            placement(Anchor::TopLeft, 0.0, (150.0, 0.0)).space(window),
+            (0.0, 100.0)
+        );
+    }
+
+    #[test]
+    fn dependency_order_puts_targets_first() {
+        let ids = [Some("date"), None, Some("clock")];
+        let targets = [Some("clock"), Some("date"), None];
+
+        let (order, targets) = dependency_order(&ids, &targets).unwrap();
+
+        assert_eq!(order, [2, 0, 1]);
+        assert_eq!(targets, [Some(2), Some(0), None]);
+    }
+
+    #[test]
+    fn dependency_order_rejects_a_cycle() {
+        let ids = [Some("a"), Some("b"), Some("c")];
+        let targets = [Some("b"), Some("c"), Some("a")];
+
+        assert!(matches!(
+            dependency_order(&ids, &targets),
+            Err(LayoutError::Cycle(names)) if names == ["a", "b", "c", "a"]
+        ));
+    }
+
+    #[test]
+    fn dependency_order_rejects_a_widget_placed_relative_to_itself() {
+        assert!(matches!(
+            dependency_order(&[Some("a")], &[Some("a")]),
+            Err(LayoutError::Cycle(names)) if names == ["a", "a"]
+        ));
+    }
+
+    #[test]
+    fn dependency_order_rejects_a_duplicate_id() {
+        assert!(matches!(
+            dependency_order(&[Some("a"), Some("a")], &[None, None]),
+            Err(LayoutError::DuplicateId(id)) if id == "a"
+        ));
+    }
+
+    #[test]
+    fn dependency_order_rejects_an_unknown_id() {
+        assert!(matches!(
+            dependency_order(&[Some("a")], &[Some("b")]),
+            Err(LayoutError::UnknownWidget(id)) if id == "b"
+        ));
+    }
+}
@@ -1,6 +1,10 @@
+mod animation;
 mod app;
 mod background;
 mod config;
+mod effects;
+mod ics;
+mod layout;
 mod render;
 mod widget;
 mod window;
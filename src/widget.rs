@@ -1,28 +1,707 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Instant;
+
+use chrono::DateTime;
+use chrono::Local;
+use chrono::NaiveDate;
+use chrono::TimeZone;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use notify::RecursiveMode;
+use notify::Watcher;
 use serde::Deserialize;
 use serde::Serialize;
 use thiserror::Error;
+use tiny_skia::ColorU8;
+use tiny_skia::Path;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoop;
+use winit::keyboard::Key;
+use winit::keyboard::NamedKey;
+
+use crate::config;
+use crate::config::MeterConfig;
+use crate::config::Thresholds;
+use crate::layout::Align;
+use crate::layout::Anchor;
+use crate::layout::Length;
+use crate::layout::Margin;
+use crate::layout::Placement;
+use crate::layout::Side;
+
+use self::agenda::Agenda;
+use self::battery::Battery;
+use self::calendar::Calendar;
+use self::clock::Clock;
+use self::command::Command;
+use self::container::Container;
+use self::date::Date;
+use self::file_text::FileText;
+use self::graph::Graph;
+use self::http::Http;
+use self::network::Network;
+use self::now_playing::NowPlaying;
+use self::panel::Panel;
+use self::picture::Picture;
+use self::pomodoro::Pomodoro;
+use self::sensors::Disk;
+use self::sensors::Temperature;
+use self::shape::Circle;
+use self::shape::Line;
+use self::shape::Rectangle;
+use self::system::draw_bar;
+use self::system::Metric;
+use self::system::SystemMonitor;
+use self::text::Text;
+use self::weather::Weather;
 
+pub mod agenda;
+pub mod battery;
+pub mod calendar;
 pub mod clock;
+pub mod command;
+pub mod container;
 pub mod date;
+pub mod file_text;
+pub mod graph;
+pub mod http;
+pub mod network;
+pub mod now_playing;
+pub mod panel;
+pub mod picture;
+pub mod pomodoro;
+pub mod relative;
+pub mod sensors;
+pub mod shape;
+pub mod system;
 pub mod text;
+pub mod weather;
+
+/// An element of the screen that can react to input and time.
+///
+/// Widgets are measured before being drawn, so the ones laying out others know how much room
+/// each child takes before drawing it at its place. Every other hook has a no-op default.
+pub trait Widget {
+    /// Where the widget goes in the window when it isn't laid out by another widget.
+    fn position(&self) -> &Position;
+
+    /// Lays the widget out to fit in `space` where it can, returning the size it is going to be
+    /// drawn at or `None` when it has nothing to draw.
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)>;
+
+    /// Draws what was last measured with its top-left corner at `origin`.
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32));
+
+    /// Handles a window event routed to this widget, returning `true` when it was consumed and
+    /// the window needs to be redrawn.
+    ///
+    /// Keyboard events are delivered to the focused widget, pointer events to the topmost widget
+    /// under the cursor, and media keys and changes to the window visibility and to the held
+    /// modifiers to every widget.
+    fn on_event(&mut self, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    /// Called after the window was resized.
+    fn on_resize(&mut self, _size: PhysicalSize<u32>) {}
+
+    /// Called when the background looks different, e.g. on a new frame of an animation.
+    fn on_background_change(&mut self) {}
+
+    /// Called whenever the event loop is woken up by a widget, right before redrawing.
+    fn on_tick(&mut self, _now: Instant) {}
+
+    /// When the widget next needs [`Widget::on_tick`] to be called, for widgets that animate
+    /// without a thread of their own waking the event loop up.
+    fn next_tick(&self) -> Option<Instant> {
+        None
+    }
+
+    /// Whether the widget can receive keyboard focus.
+    fn focusable(&self) -> bool {
+        false
+    }
+}
+
+/// Creates the widget described by `config`.
+pub fn from_config(
+    event_loop: &EventLoop<()>,
+    config: config::Widget,
+) -> Result<Box<dyn Widget>, WidgetError> {
+    fn cast_box<W: Widget + 'static>(widget: W) -> Box<dyn Widget> {
+        let widget: Box<dyn Widget> = Box::new(widget);
+        widget
+    }
+
+    match config {
+        config::Widget::Clock(config) => Clock::new(event_loop, config).map(cast_box),
+
+        config::Widget::Text(config) => Text::new(config).map(cast_box),
+
+        config::Widget::Date(config) => Date::new(event_loop, config).map(cast_box),
+
+        config::Widget::Pomodoro(config) => Pomodoro::new(config).map(cast_box),
+
+        config::Widget::Command(config) => Command::new(event_loop, config).map(cast_box),
+
+        config::Widget::FileText(config) => FileText::new(event_loop, config).map(cast_box),
+
+        config::Widget::Battery(config) => Battery::new(event_loop, config).map(cast_box),
+
+        config::Widget::Cpu(config) => {
+            SystemMonitor::new(event_loop, Metric::Cpu, config).map(cast_box)
+        }
+
+        config::Widget::Memory(config) => {
+            SystemMonitor::new(event_loop, Metric::Memory, config).map(cast_box)
+        }
+
+        config::Widget::LoadAverage(config) => {
+            SystemMonitor::new(event_loop, Metric::LoadAverage, config).map(cast_box)
+        }
+
+        config::Widget::Uptime(config) => {
+            SystemMonitor::new(event_loop, Metric::Uptime, config).map(cast_box)
+        }
+
+        config::Widget::Graph(config) => Graph::new(event_loop, config).map(cast_box),
+
+        config::Widget::Disk(config) => Disk::new(event_loop, config).map(cast_box),
+
+        config::Widget::Temperature(config) => Temperature::new(event_loop, config).map(cast_box),
+
+        config::Widget::Network(config) => Network::new(event_loop, config).map(cast_box),
+
+        config::Widget::Weather(config) => Weather::new(event_loop, config).map(cast_box),
+
+        config::Widget::Http(config) => Http::new(event_loop, config).map(cast_box),
+
+        config::Widget::Agenda(config) => Agenda::new(event_loop, config).map(cast_box),
+
+        config::Widget::Calendar(config) => Calendar::new(event_loop, config).map(cast_box),
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+        config::Widget::NowPlaying(config) => NowPlaying::new(event_loop, config).map(cast_box),
+
+        config::Widget::Image(config) => Picture::new(config).map(cast_box),
+
+        config::Widget::Rectangle(config) => Ok(cast_box(Rectangle::new(config))),
+
+        config::Widget::Circle(config) => Ok(cast_box(Circle::new(config))),
+
+        config::Widget::Line(config) => Ok(cast_box(Line::new(config))),
+
+        config::Widget::Panel(config) => Panel::new(event_loop, config).map(cast_box),
+
+        config::Widget::Row(config) => Container::row(event_loop, config).map(cast_box),
+
+        config::Widget::Column(config) => Container::column(event_loop, config).map(cast_box),
+
+        config::Widget::Stack(config) => Container::stack(event_loop, config).map(cast_box),
+
+        config::Widget::Grid(config) => Container::grid(event_loop, config).map(cast_box),
+    }
+}
+
+/// Rectangle in window coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Bounds {
+    /// Area of a box of `size` with its top-left corner at `origin`.
+    pub fn new(origin: (i32, i32), (width, height): (f32, f32)) -> Self {
+        Bounds {
+            x: origin.0 as f32,
+            y: origin.1 as f32,
+            width,
+            height,
+        }
+    }
+
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Where a widget goes in the window, resolved against the window size on every frame.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(tag = "position")]
 pub enum Position {
     #[default]
     Center,
     CenteredX {
-        y: u32,
+        y: i32,
     },
     CenteredY {
-        x: u32,
+        x: i32,
     },
     XY {
-        x: u32,
-        y: u32,
+        x: i32,
+        y: i32,
     },
+    /// Lines up one of the nine points of the widget with the same point of the window, e.g. the
+    /// bottom-right corners, then moves it by `x` and `y`.
+    Anchored {
+        #[serde(default)]
+        anchor: Anchor,
+        #[serde(default)]
+        margin: Margin,
+        #[serde(default)]
+        x: Length,
+        #[serde(default)]
+        y: Length,
+    },
+    /// Places the widget next to the top-level widget with the `id` given in `relative_to`, e.g.
+    /// below it and centered. Widgets inside a panel or a container can't be placed this way.
+    RelativeTo {
+        relative_to: String,
+        #[serde(default)]
+        side: Side,
+        /// Space between the two widgets.
+        #[serde(default)]
+        gap: Length,
+        /// How the widget is lined up along the side of the other one.
+        #[serde(default)]
+        align: Align,
+    },
+}
+
+impl Position {
+    /// Computes the top-left corner of a widget of the given size inside the window.
+    pub fn resolve(&self, window: PhysicalSize<u32>, size: (f32, f32)) -> (i32, i32) {
+        let (x, y) = self.placement().resolve(window, size);
+
+        (x.round() as i32, y.round() as i32)
+    }
+
+    /// Largest size a widget can have and still fit in the window where it is placed.
+    pub fn space(&self, window: PhysicalSize<u32>) -> (f32, f32) {
+        self.placement().space(window)
+    }
+
+    fn placement(&self) -> Placement {
+        let pixels = |pixels: i32| Length::Px(pixels as f32);
+
+        let (anchor, margin, offset) = match *self {
+            Position::Center => (Anchor::Center, Margin::default(), Default::default()),
+            Position::CenteredX { y } => (
+                Anchor::Top,
+                Margin::default(),
+                (Length::default(), pixels(y)),
+            ),
+            Position::CenteredY { x } => (
+                Anchor::Left,
+                Margin::default(),
+                (pixels(x), Length::default()),
+            ),
+            Position::XY { x, y } => (Anchor::TopLeft, Margin::default(), (pixels(x), pixels(y))),
+            Position::Anchored {
+                anchor,
+                margin,
+                x,
+                y,
+            } => (anchor, margin, (x, y)),
+            // The application moves the widget next to the other one once that one is placed.
+            Position::RelativeTo { .. } => (Anchor::Center, Margin::default(), Default::default()),
+        };
+
+        Placement {
+            anchor,
+            margin,
+            offset,
+        }
+    }
+}
+
+/// Keeps only the last `lines` lines of `text`, or all of them when no limit is given.
+fn last_lines(text: &str, lines: Option<usize>) -> String {
+    match lines {
+        Some(lines) => {
+            let all: Vec<&str> = text.lines().collect();
+            all[all.len().saturating_sub(lines)..].join("\n")
+        }
+        None => text.to_string(),
+    }
+}
+
+/// Calls `on_change` from a background thread every time one of the files changes on disk.
+fn watch_files(
+    paths: Vec<PathBuf>,
+    mut on_change: impl FnMut() + Send + 'static,
+) -> Result<(), WidgetError> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(|_| WidgetError)?;
+
+    // Editors and build tools usually replace files instead of writing to them, which drops the
+    // watch on the file itself, so the parent directories are watched instead.
+    let mut directories: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        })
+        .collect();
+    directories.sort();
+    directories.dedup();
+
+    for directory in &directories {
+        watcher
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|_| WidgetError)?;
+    }
+
+    let file_names: Vec<_> = paths
+        .iter()
+        .filter_map(|path| path.file_name().map(ToOwned::to_owned))
+        .collect();
+
+    std::thread::spawn(move || {
+        // The watcher stops once dropped, so it lives as long as this thread.
+        let _watcher = watcher;
+
+        for event in receiver {
+            let Ok(event) = event else {
+                continue;
+            };
+
+            let touches_file = event.paths.iter().any(|changed| {
+                changed
+                    .file_name()
+                    .is_some_and(|name| file_names.iter().any(|file_name| file_name == name))
+            });
+
+            if !touches_file || event.kind.is_access() {
+                continue;
+            }
+
+            on_change();
+        }
+    });
+
+    Ok(())
+}
+
+/// Local midnight at the start of `day`, or the first time after it on days starting with a
+/// daylight saving time change.
+fn start_of_day(day: NaiveDate) -> Option<DateTime<Local>> {
+    Local
+        .from_local_datetime(&day.and_hms_opt(0, 0, 0)?)
+        .earliest()
+}
+
+/// When the local clock will show `time`, right away if it already has.
+fn instant_at(time: DateTime<Local>) -> Instant {
+    Instant::now() + (time - Local::now()).to_std().unwrap_or_default()
+}
+
+/// Space between a line of text and the bar below it.
+const BAR_SPACING: f32 = 4.0;
+
+/// Space between two rows.
+const ROW_SPACING: f32 = 8.0;
+
+/// A line of text, optionally followed by a bar.
+struct Row {
+    text: String,
+    color: cosmic_text::Color,
+    fraction: Option<f32>,
+}
+
+/// Lines of text below each other, each optionally followed by a bar.
+#[derive(Default)]
+struct Rows {
+    rows: Vec<Row>,
+    /// Space the rows were measured in.
+    space: (f32, f32),
+    /// Where each row starts below the first one.
+    tops: Vec<f32>,
+}
+
+impl Rows {
+    /// Lays `rows` out below each other in `space`, returning the size they take.
+    fn measure(
+        &mut self,
+        text_widget: &mut Text,
+        rows: Vec<Row>,
+        space: (f32, f32),
+        bar: Option<&MeterConfig>,
+    ) -> Option<(f32, f32)> {
+        self.rows = rows;
+        self.space = space;
+        self.tops.clear();
+
+        let mut width = 0.0f32;
+        let mut top = 0.0;
+
+        for row in &self.rows {
+            text_widget.update_data(row.text.clone());
+            let (text_width, text_height) = text_widget.layout(space);
+
+            let (row_width, row_height) = match (bar, row.fraction) {
+                (Some(bar), Some(_)) => (
+                    text_width.max(bar.width),
+                    text_height + BAR_SPACING + bar.height,
+                ),
+                _ => (text_width, text_height),
+            };
+
+            self.tops.push(top);
+            width = width.max(row_width);
+            top += row_height + ROW_SPACING;
+        }
+
+        match self.rows.is_empty() {
+            true => None,
+            false => Some((width, top - ROW_SPACING)),
+        }
+    }
+
+    /// Draws the measured rows below each other from `origin`.
+    fn draw(
+        &self,
+        text_widget: &mut Text,
+        buffer: &mut Pixmap,
+        (x, y): (i32, i32),
+        bar: Option<&MeterConfig>,
+    ) {
+        for (row, top) in self.rows.iter().zip(&self.tops) {
+            text_widget.update_data(row.text.clone());
+            text_widget.set_color(row.color);
+            let (_, text_height) = text_widget.layout(self.space);
+            let row_y = y as f32 + top;
+
+            text_widget.paint(buffer, x, row_y as i32);
+
+            if let (Some(bar), Some(fraction)) = (bar, row.fraction) {
+                let area = Rect::from_xywh(
+                    x as f32,
+                    row_y + text_height + BAR_SPACING,
+                    bar.width,
+                    bar.height,
+                );
+
+                if let Some(area) = area {
+                    draw_bar(buffer, area, bar, fraction);
+                }
+            }
+        }
+    }
+}
+
+/// Scales `image` to cover `width` x `height`, cropping what does not fit, and converts it to
+/// the premultiplied format tiny-skia draws with.
+/// Whether `key` controls media playback, those keys go to every widget whatever has focus.
+pub fn is_media_key(key: &Key) -> bool {
+    matches!(
+        key,
+        Key::Named(
+            NamedKey::MediaPlay
+                | NamedKey::MediaPause
+                | NamedKey::MediaPlayPause
+                | NamedKey::MediaStop
+                | NamedKey::MediaTrackNext
+                | NamedKey::MediaTrackPrevious
+        )
+    )
+}
+
+fn image_pixmap(image: &DynamicImage, width: u32, height: u32) -> Option<Pixmap> {
+    let resized = image
+        .resize_to_fill(width, height, FilterType::Triangle)
+        .into_rgba8();
+
+    let mut pixmap = Pixmap::new(resized.width(), resized.height())?;
+    for (pixel, rgba) in pixmap.pixels_mut().iter_mut().zip(resized.pixels()) {
+        let [r, g, b, a] = rgba.0;
+        *pixel = ColorU8::from_rgba(r, g, b, a).premultiply();
+    }
+
+    Some(pixmap)
+}
+
+/// Outlines `rect` with its corners rounded by `radius`, which is limited to half the shortest
+/// side.
+fn rounded_rect(rect: Rect, radius: f32) -> Option<Path> {
+    let radius = radius.min(rect.width() / 2.0).min(rect.height() / 2.0);
+    if radius <= 0.0 {
+        return Some(PathBuilder::from_rect(rect));
+    }
+
+    // Distance of the control points from the corners for a cubic close to a quarter circle.
+    let control = radius * (1.0 - 0.552_284_8);
+    let (left, top, right, bottom) = (rect.left(), rect.top(), rect.right(), rect.bottom());
+
+    let mut path = PathBuilder::new();
+    path.move_to(left + radius, top);
+    path.line_to(right - radius, top);
+    path.cubic_to(
+        right - control,
+        top,
+        right,
+        top + control,
+        right,
+        top + radius,
+    );
+    path.line_to(right, bottom - radius);
+    path.cubic_to(
+        right,
+        bottom - control,
+        right - control,
+        bottom,
+        right - radius,
+        bottom,
+    );
+    path.line_to(left + radius, bottom);
+    path.cubic_to(
+        left + control,
+        bottom,
+        left,
+        bottom - control,
+        left,
+        bottom - radius,
+    );
+    path.line_to(left, top + radius);
+    path.cubic_to(left, top + control, left + control, top, left + radius, top);
+    path.close();
+
+    path.finish()
+}
+
+/// Formats a number of bytes with binary units, e.g. `1.5 GiB`.
+fn human_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{value:.0} {}", UNITS[unit]),
+        _ => format!("{value:.1} {}", UNITS[unit]),
+    }
+}
+
+/// Picks the text color for `value`, falling back to `color` while it is within the thresholds.
+fn threshold_color(
+    thresholds: &Thresholds,
+    value: f32,
+    color: cosmic_text::Color,
+) -> cosmic_text::Color {
+    let lower_is_worse = thresholds.critical < thresholds.warning;
+    let reached = |threshold: f32| match lower_is_worse {
+        true => value <= threshold,
+        false => value >= threshold,
+    };
+
+    let (r, g, b, a) = if reached(thresholds.critical) {
+        thresholds.critical_color
+    } else if reached(thresholds.warning) {
+        thresholds.warning_color
+    } else {
+        return color;
+    };
+
+    cosmic_text::Color::rgba(r, g, b, a)
 }
 
 #[derive(Debug, Error)]
 #[error("widget error")]
 pub struct WidgetError;
+
+/// An empty directory for a test's fixtures, removed with everything in it when dropped.
+#[cfg(test)]
+pub(crate) struct TempDir(PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    /// `name` has to be unique across tests, e.g. the widget followed by the test.
+    pub(crate) fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("russ-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+
+        Self(path)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempDir {
+    type Target = std::path::Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_resolve_in_the_window() {
+        let window = PhysicalSize::new(100, 50);
+
+        assert_eq!(Position::Center.resolve(window, (20.0, 10.0)), (40, 20));
+        assert_eq!(
+            Position::CenteredX { y: 5 }.resolve(window, (20.0, 10.0)),
+            (40, 5)
+        );
+        assert_eq!(
+            Position::CenteredY { x: 5 }.resolve(window, (20.0, 10.0)),
+            (5, 20)
+        );
+        // Text wider and taller than the window stays centered, past both edges.
+        assert_eq!(Position::Center.resolve(window, (121.0, 70.0)), (-11, -10));
+    }
+
+    #[test]
+    fn negative_xy_goes_past_the_top_left_corner() {
+        let window = PhysicalSize::new(100, 50);
+        let position = Position::XY { x: -5, y: -7 };
+
+        assert_eq!(position.resolve(window, (20.0, 10.0)), (-5, -7));
+        assert_eq!(position.space(window), (100.0, 50.0));
+    }
+
+    #[test]
+    fn positions_in_tiny_windows() {
+        for window in [PhysicalSize::new(1, 1), PhysicalSize::new(0, 0)] {
+            let side = window.width as f32;
+
+            assert_eq!(Position::Center.space(window), (side, side));
+            assert_eq!(
+                Position::XY { x: 0, y: 0 }.resolve(window, (8.0, 8.0)),
+                (0, 0)
+            );
+            assert_eq!(
+                Position::XY { x: 3, y: 3 }.space(window),
+                (0.0, 0.0),
+                "{window:?}"
+            );
+        }
+
+        assert_eq!(
+            Position::Center.resolve(PhysicalSize::new(0, 0), (8.0, 4.0)),
+            (-4, -2)
+        );
+    }
+}
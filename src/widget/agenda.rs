@@ -0,0 +1,209 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::DateTime;
+use chrono::Days;
+use chrono::Local;
+use cosmic_text::Color;
+use spin::RwLock;
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::AgendaConfig;
+use crate::config::TextConfig;
+use crate::ics::Calendar;
+use crate::ics::Event;
+
+use super::instant_at;
+use super::start_of_day;
+use super::text::Text;
+use super::watch_files;
+use super::Position;
+use super::Row;
+use super::Rows;
+use super::Widget;
+use super::WidgetError;
+
+pub struct Agenda {
+    text_widget: Text,
+    text_color: Color,
+    highlight_color: Color,
+    position: Position,
+    events: usize,
+    include_tomorrow: bool,
+    time_format: String,
+    empty_text: String,
+    calendar: Arc<RwLock<Calendar>>,
+    rows: Rows,
+    /// When the agenda as last measured goes out of date, as an event starts or ends or the day
+    /// changes.
+    next_change: Option<DateTime<Local>>,
+}
+
+impl Agenda {
+    pub fn new(event_loop: &EventLoop<()>, config: AgendaConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.font.color;
+        let text_color = Color::rgba(r, g, b, a);
+        let (r, g, b, a) = config.highlight_color;
+        let highlight_color = Color::rgba(r, g, b, a);
+
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let files: Vec<PathBuf> = config.files.iter().map(PathBuf::from).collect();
+        let calendar = Arc::new(RwLock::new(Calendar::load(&files)));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let calendar = calendar.clone();
+            let paths = files.clone();
+
+            watch_files(files, move || {
+                *calendar.write() = Calendar::load(&paths);
+                event_loop_proxy.send_event(()).unwrap();
+            })?;
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            text_color,
+            highlight_color,
+            position: config.position,
+            events: config.events,
+            include_tomorrow: config.include_tomorrow,
+            time_format: config.time_format,
+            empty_text: config.empty_text,
+            calendar,
+            rows: Rows::default(),
+            next_change: None,
+        })
+    }
+}
+
+impl Widget for Agenda {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let now = Local::now();
+        let today = now.date_naive();
+        let tomorrow = today + Days::new(1);
+
+        let days = match self.include_tomorrow {
+            true => 2,
+            false => 1,
+        };
+        let until = start_of_day(today + Days::new(days))?;
+
+        let events = self.calendar.read().events_between(now, until);
+        self.next_change = next_change(&events, now, start_of_day(tomorrow)?);
+
+        let mut rows: Vec<Row> = events
+            .iter()
+            .take(self.events)
+            .map(|event| {
+                let day = match event.start.date_naive() == tomorrow {
+                    true => "Tomorrow ",
+                    false => "",
+                };
+
+                let time = match event.all_day {
+                    true => "All day".to_string(),
+                    false => format!(
+                        "{}–{}",
+                        event.start.format(&self.time_format),
+                        event.end.format(&self.time_format)
+                    ),
+                };
+
+                let color = match event.is_ongoing(now) {
+                    true => self.highlight_color,
+                    false => self.text_color,
+                };
+
+                Row {
+                    text: format!("{day}{time}  {}", event.summary),
+                    color,
+                    fraction: None,
+                }
+            })
+            .collect();
+
+        if rows.is_empty() {
+            rows.push(Row {
+                text: self.empty_text.clone(),
+                color: self.text_color,
+                fraction: None,
+            });
+        }
+
+        self.rows.measure(&mut self.text_widget, rows, space, None)
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.rows.draw(&mut self.text_widget, buffer, origin, None);
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.next_change.map(instant_at)
+    }
+}
+
+/// The first start or end of one of `events` after `now`, or `midnight` if that comes first.
+fn next_change(
+    events: &[Event],
+    now: DateTime<Local>,
+    midnight: DateTime<Local>,
+) -> Option<DateTime<Local>> {
+    events
+        .iter()
+        .flat_map(|event| [event.start, event.end])
+        .filter(|&time| time > now)
+        .chain([midnight])
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn event(start: DateTime<Local>, minutes: i64) -> Event {
+        Event {
+            summary: "Meeting".to_string(),
+            start,
+            end: start + Duration::minutes(minutes),
+            all_day: false,
+        }
+    }
+
+    #[test]
+    fn agenda_changes_when_the_next_event_starts_or_ends() {
+        let now = Local::now();
+        let midnight = now + Duration::hours(5);
+
+        let ongoing = event(now - Duration::minutes(10), 30);
+        let later = event(now + Duration::hours(1), 30);
+
+        assert_eq!(
+            next_change(&[ongoing.clone(), later.clone()], now, midnight),
+            Some(now + Duration::minutes(20))
+        );
+        assert_eq!(
+            next_change(&[later], now, midnight),
+            Some(now + Duration::hours(1))
+        );
+        // Without events coming up, the agenda still moves on to the next day.
+        assert_eq!(next_change(&[], now, midnight), Some(midnight));
+
+        let overnight = event(now + Duration::hours(4), 120);
+        assert_eq!(
+            next_change(&[overnight], now + Duration::hours(4), midnight),
+            Some(midnight)
+        );
+    }
+}
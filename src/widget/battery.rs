@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_text::Color;
+use spin::RwLock;
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::BatteryConfig;
+use crate::config::TextConfig;
+use crate::config::Thresholds;
+
+use super::text::Text;
+use super::threshold_color;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChargingState {
+    Charging,
+    Discharging,
+    Full,
+    NotCharging,
+    Unknown,
+}
+
+impl ChargingState {
+    fn label(&self) -> &'static str {
+        match self {
+            ChargingState::Charging => "charging",
+            ChargingState::Discharging => "discharging",
+            ChargingState::Full => "full",
+            ChargingState::NotCharging => "not charging",
+            ChargingState::Unknown => "",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct BatteryStatus {
+    capacity: u8,
+    state: ChargingState,
+    /// Time until empty while discharging, or until full while charging.
+    time_remaining: Option<Duration>,
+}
+
+pub struct Battery {
+    text_widget: Text,
+    text_color: Color,
+    format: String,
+    thresholds: Thresholds,
+    status: Arc<RwLock<Option<BatteryStatus>>>,
+}
+
+impl Battery {
+    pub fn new(event_loop: &EventLoop<()>, config: BatteryConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.font.color;
+        let text_color = Color::rgba(r, g, b, a);
+
+        let text_config = TextConfig::new(String::new(), config.position, config.font);
+        let widget = Text::new(text_config)?;
+
+        let root = PathBuf::from(config.root);
+        let status = Arc::new(RwLock::new(read_battery(&root, config.name.as_deref())));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let status = status.clone();
+            let name = config.name;
+            let interval = Duration::from_secs(config.interval);
+
+            std::thread::spawn(move || loop {
+                std::thread::sleep(interval);
+
+                *status.write() = read_battery(&root, name.as_deref());
+                event_loop_proxy.send_event(()).unwrap();
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            text_color,
+            format: config.format,
+            thresholds: config.thresholds,
+            status,
+        })
+    }
+}
+
+impl Widget for Battery {
+    fn position(&self) -> &Position {
+        self.text_widget.position()
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let (text, color) = match &*self.status.read() {
+            Some(status) => {
+                let time = status
+                    .time_remaining
+                    .map(|time| {
+                        let minutes = time.as_secs() / 60;
+                        format!("{}:{:02}", minutes / 60, minutes % 60)
+                    })
+                    .unwrap_or_default();
+
+                let text = self
+                    .format
+                    .replace("{capacity}", &status.capacity.to_string())
+                    .replace("{status}", status.state.label())
+                    .replace("{time}", &time);
+
+                let color = match status.state {
+                    ChargingState::Charging | ChargingState::Full => self.text_color,
+                    _ => threshold_color(&self.thresholds, status.capacity as f32, self.text_color),
+                };
+
+                (text.trim().to_string(), color)
+            }
+
+            None => ("no battery".to_string(), self.text_color),
+        };
+
+        self.text_widget.update_data(text);
+        self.text_widget.set_color(color);
+        self.text_widget.measure(window, space)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.text_widget.draw_at(window, buffer, origin);
+    }
+}
+
+/// Reads the named power supply under `root`, or the first battery found there.
+fn read_battery(root: &Path, name: Option<&str>) -> Option<BatteryStatus> {
+    if let Some(name) = name {
+        return read_uevent(&root.join(name)).and_then(|uevent| parse_uevent(&uevent));
+    }
+
+    let mut supplies: Vec<PathBuf> = std::fs::read_dir(root)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    supplies.sort();
+
+    supplies
+        .iter()
+        .filter_map(|supply| read_uevent(supply))
+        .filter(|uevent| uevent.get("TYPE").map(String::as_str) == Some("Battery"))
+        .find_map(|uevent| parse_uevent(&uevent))
+}
+
+/// Reads the `KEY=value` lines of a power supply `uevent` file, without the `POWER_SUPPLY_`
+/// prefix.
+fn read_uevent(supply: &Path) -> Option<HashMap<String, String>> {
+    let contents = std::fs::read_to_string(supply.join("uevent")).ok()?;
+
+    let uevent = contents
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let key = key.strip_prefix("POWER_SUPPLY_").unwrap_or(key);
+            (key.to_string(), value.trim().to_string())
+        })
+        .collect();
+
+    Some(uevent)
+}
+
+fn parse_uevent(uevent: &HashMap<String, String>) -> Option<BatteryStatus> {
+    let number = |key: &str| uevent.get(key).and_then(|value| value.parse::<f64>().ok());
+
+    let state = match uevent.get("STATUS").map(String::as_str) {
+        Some("Charging") => ChargingState::Charging,
+        Some("Discharging") => ChargingState::Discharging,
+        Some("Full") => ChargingState::Full,
+        Some("Not charging") => ChargingState::NotCharging,
+        _ => ChargingState::Unknown,
+    };
+
+    // Batteries report either energy (µWh, µW) or charge (µAh, µA), the ratios are the same.
+    let (now, full, rate) = match number("ENERGY_NOW") {
+        Some(now) => (Some(now), number("ENERGY_FULL"), number("POWER_NOW")),
+        None => (
+            number("CHARGE_NOW"),
+            number("CHARGE_FULL"),
+            number("CURRENT_NOW"),
+        ),
+    };
+
+    let capacity = match (number("CAPACITY"), now, full) {
+        (Some(capacity), _, _) => capacity,
+        (None, Some(now), Some(full)) if full > 0.0 => now / full * 100.0,
+        _ => return None,
+    };
+
+    let hours = match (state, now, full, rate) {
+        (_, _, _, Some(rate)) if rate <= 0.0 => None,
+        (ChargingState::Discharging, Some(now), _, Some(rate)) => Some(now / rate),
+        (ChargingState::Charging, Some(now), Some(full), Some(rate)) => {
+            Some((full - now).max(0.0) / rate)
+        }
+        _ => None,
+    };
+
+    Some(BatteryStatus {
+        capacity: capacity.clamp(0.0, 100.0).round() as u8,
+        state,
+        time_remaining: hours.map(|hours| Duration::from_secs_f64(hours * 3600.0)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    /// A `power_supply` directory holding the given supplies and their `uevent` files.
+    fn power_supply(test: &str, supplies: &[(&str, &str)]) -> TempDir {
+        let root = TempDir::new(&format!("battery-{test}"));
+
+        for (name, uevent) in supplies {
+            std::fs::create_dir_all(root.join(name)).unwrap();
+            std::fs::write(root.join(name).join("uevent"), uevent).unwrap();
+        }
+
+        root
+    }
+
+    const MAINS: &str = "POWER_SUPPLY_NAME=AC\nPOWER_SUPPLY_TYPE=Mains\nPOWER_SUPPLY_ONLINE=1\n";
+
+    #[test]
+    fn charging_battery_reports_time_until_full() {
+        let root = power_supply(
+            "charging",
+            &[
+                ("AC", MAINS),
+                (
+                    "BAT0",
+                    "POWER_SUPPLY_TYPE=Battery\n\
+                     POWER_SUPPLY_STATUS=Charging\n\
+                     POWER_SUPPLY_CAPACITY=50\n\
+                     POWER_SUPPLY_ENERGY_NOW=25000000\n\
+                     POWER_SUPPLY_ENERGY_FULL=50000000\n\
+                     POWER_SUPPLY_POWER_NOW=12500000\n",
+                ),
+            ],
+        );
+
+        let status = read_battery(&root, None).unwrap();
+
+        assert_eq!(status.capacity, 50);
+        assert_eq!(status.state, ChargingState::Charging);
+        assert_eq!(status.time_remaining, Some(Duration::from_secs(2 * 3600)));
+    }
+
+    #[test]
+    fn discharging_battery_reports_time_until_empty() {
+        let root = power_supply(
+            "discharging",
+            &[(
+                "BAT1",
+                "POWER_SUPPLY_TYPE=Battery\n\
+                 POWER_SUPPLY_STATUS=Discharging\n\
+                 POWER_SUPPLY_CAPACITY=75\n\
+                 POWER_SUPPLY_CHARGE_NOW=3000000\n\
+                 POWER_SUPPLY_CHARGE_FULL=4000000\n\
+                 POWER_SUPPLY_CURRENT_NOW=2000000\n",
+            )],
+        );
+
+        let status = read_battery(&root, Some("BAT1")).unwrap();
+
+        assert_eq!(status.capacity, 75);
+        assert_eq!(status.state, ChargingState::Discharging);
+        assert_eq!(status.time_remaining, Some(Duration::from_secs(5400)));
+    }
+
+    #[test]
+    fn capacity_is_computed_when_missing() {
+        let root = power_supply(
+            "no-capacity",
+            &[(
+                "BAT0",
+                "POWER_SUPPLY_TYPE=Battery\n\
+                 POWER_SUPPLY_STATUS=Discharging\n\
+                 POWER_SUPPLY_ENERGY_NOW=10000000\n\
+                 POWER_SUPPLY_ENERGY_FULL=40000000\n\
+                 POWER_SUPPLY_POWER_NOW=0\n",
+            )],
+        );
+
+        let status = read_battery(&root, None).unwrap();
+
+        assert_eq!(status.capacity, 25);
+        assert_eq!(status.time_remaining, None);
+    }
+
+    #[test]
+    fn battery_without_any_charge_is_ignored() {
+        let root = power_supply(
+            "no-charge",
+            &[(
+                "BAT0",
+                "POWER_SUPPLY_TYPE=Battery\nPOWER_SUPPLY_STATUS=Unknown\n",
+            )],
+        );
+
+        assert!(read_battery(&root, None).is_none());
+    }
+
+    #[test]
+    fn no_battery_present() {
+        let root = power_supply("none", &[("AC", MAINS)]);
+
+        assert!(read_battery(&root, None).is_none());
+        assert!(read_battery(&root, Some("BAT0")).is_none());
+        assert!(read_battery(&root.join("missing"), None).is_none());
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Instant;
+
+use chrono::Datelike;
+use chrono::Days;
+use chrono::Local;
+use chrono::Months;
+use chrono::NaiveDate;
+use chrono::Weekday;
+use spin::RwLock;
+use tiny_skia::Color;
+use tiny_skia::FillRule;
+use tiny_skia::Paint;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::CalendarConfig;
+use crate::config::TextConfig;
+use crate::config::WeekStart;
+use crate::ics;
+
+use super::instant_at;
+use super::start_of_day;
+use super::text::Text;
+use super::watch_files;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// Weeks shown in the grid, enough for any month.
+const WEEKS: usize = 6;
+
+pub struct Calendar {
+    text_widget: Text,
+    position: Position,
+    week_start: WeekStart,
+    week_numbers: bool,
+    today_color: Color,
+    event_color: Color,
+    events: Arc<RwLock<ics::Calendar>>,
+    /// Space the calendar was measured in and the size of its cells.
+    space: (f32, f32),
+    cell: (f32, f32),
+}
+
+impl Calendar {
+    pub fn new(event_loop: &EventLoop<()>, config: CalendarConfig) -> Result<Self, WidgetError> {
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let files: Vec<PathBuf> = config.files.iter().map(PathBuf::from).collect();
+        let events = Arc::new(RwLock::new(ics::Calendar::load(&files)));
+
+        if !files.is_empty() {
+            let event_loop_proxy = event_loop.create_proxy();
+            let events = events.clone();
+            let paths = files.clone();
+
+            watch_files(files, move || {
+                *events.write() = ics::Calendar::load(&paths);
+                event_loop_proxy.send_event(()).unwrap();
+            })?;
+        }
+
+        let (r, g, b, a) = config.today_color;
+        let today_color = Color::from_rgba8(r, g, b, a);
+        let (r, g, b, a) = config.event_color;
+        let event_color = Color::from_rgba8(r, g, b, a);
+
+        Ok(Self {
+            text_widget: widget,
+            position: config.position,
+            week_start: config.week_start,
+            week_numbers: config.week_numbers,
+            today_color,
+            event_color,
+            events,
+            space: (0.0, 0.0),
+            cell: (0.0, 0.0),
+        })
+    }
+
+    /// Number of cells in a row, with the week numbers.
+    fn columns(&self) -> usize {
+        7 + usize::from(self.week_numbers)
+    }
+
+    /// Draws `text` centered inside the cell whose top-left corner is at `(x, y)`.
+    fn draw_cell(
+        &mut self,
+        buffer: &mut Pixmap,
+        text: String,
+        (x, y): (f32, f32),
+        cell: (f32, f32),
+    ) {
+        self.text_widget.update_data(text);
+        let (width, height) = self.text_widget.layout(self.space);
+
+        self.text_widget.paint(
+            buffer,
+            (x + (cell.0 - width) / 2.0) as i32,
+            (y + (cell.1 - height) / 2.0) as i32,
+        );
+    }
+}
+
+impl Widget for Calendar {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        self.space = space;
+
+        // Every cell is as wide as the widest two digit number, with some breathing room.
+        self.text_widget.update_data("00".to_string());
+        let (digits_width, _) = self.text_widget.layout(space);
+        let font_size = self.text_widget.font_size();
+        self.cell = (digits_width + font_size * 0.8, font_size * 1.6);
+
+        // Title, weekday names and the weeks.
+        Some((
+            self.cell.0 * self.columns() as f32,
+            self.cell.1 * (WEEKS + 2) as f32,
+        ))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let today = Local::now().date_naive();
+        let Some(first_of_month) = today.with_day(1) else {
+            return;
+        };
+
+        let grid_start = grid_start(first_of_month, self.week_start);
+        let grid_end = grid_start + Days::new(WEEKS as u64 * 7 - 1);
+        let marked = days_with_events(&self.events.read(), grid_start, grid_end);
+
+        let cell = self.cell;
+        let font_size = self.text_widget.font_size();
+        let width = cell.0 * self.columns() as f32;
+        let (x, y) = (x as f32, y as f32);
+        let days_x = match self.week_numbers {
+            true => x + cell.0,
+            false => x,
+        };
+
+        let title = first_of_month.format("%B %Y").to_string();
+        self.draw_cell(buffer, title, (x, y), (width, cell.1));
+
+        let weekdays = (0..7).map(|offset| grid_start + Days::new(offset));
+        for (column, day) in weekdays.enumerate() {
+            let name = day.format("%a").to_string().chars().take(2).collect();
+            let position = (days_x + cell.0 * column as f32, y + cell.1);
+            self.draw_cell(buffer, name, position, cell);
+        }
+
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Default::default()
+        };
+
+        for week in 0..WEEKS {
+            let row_y = y + cell.1 * (week + 2) as f32;
+            let week_start = grid_start + Days::new(week as u64 * 7);
+
+            if self.week_numbers {
+                let number = week_start.iso_week().week().to_string();
+                self.draw_cell(buffer, number, (x, row_y), cell);
+            }
+
+            for column in 0..7 {
+                let day = week_start + Days::new(column);
+                if day < first_of_month || day >= first_of_month + Months::new(1) {
+                    continue;
+                }
+
+                let cell_x = days_x + cell.0 * column as f32;
+                let center = (cell_x + cell.0 / 2.0, row_y + cell.1 / 2.0);
+
+                if day == today {
+                    let mut circle = PathBuilder::new();
+                    circle.push_circle(center.0, center.1, cell.1.min(cell.0) / 2.0);
+
+                    if let Some(circle) = circle.finish() {
+                        paint.set_color(self.today_color);
+                        buffer.fill_path(
+                            &circle,
+                            &paint,
+                            FillRule::Winding,
+                            Transform::identity(),
+                            None,
+                        );
+                    }
+                }
+
+                if marked.contains(&day) {
+                    let mut dot = PathBuilder::new();
+                    dot.push_circle(center.0, row_y + cell.1 * 0.92, font_size * 0.08);
+
+                    if let Some(dot) = dot.finish() {
+                        paint.set_color(self.event_color);
+                        buffer.fill_path(
+                            &dot,
+                            &paint,
+                            FillRule::Winding,
+                            Transform::identity(),
+                            None,
+                        );
+                    }
+                }
+
+                self.draw_cell(buffer, day.day().to_string(), (cell_x, row_y), cell);
+            }
+        }
+    }
+    /// Today moves at midnight.
+    fn next_tick(&self) -> Option<Instant> {
+        let tomorrow = Local::now().date_naive() + Days::new(1);
+
+        start_of_day(tomorrow).map(instant_at)
+    }
+}
+
+/// First day of the grid showing the month starting on `first_of_month`.
+fn grid_start(first_of_month: NaiveDate, week_start: WeekStart) -> NaiveDate {
+    let first_weekday = match week_start {
+        WeekStart::Monday => Weekday::Mon,
+        WeekStart::Sunday => Weekday::Sun,
+    };
+
+    first_of_month.week(first_weekday).first_day()
+}
+
+/// Days between `first` and `last` (inclusive) with at least one event.
+fn days_with_events(
+    calendar: &ics::Calendar,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> HashSet<NaiveDate> {
+    let (Some(from), Some(to)) = (start_of_day(first), start_of_day(last + Days::new(1))) else {
+        return HashSet::new();
+    };
+
+    let mut days = HashSet::new();
+
+    for event in calendar.events_between(from, to) {
+        let mut day = event.start.date_naive().max(first);
+        // The end is exclusive, an event ending at midnight doesn't mark the next day.
+        let end = (event.end - chrono::Duration::seconds(1))
+            .date_naive()
+            .max(day);
+
+        while day <= end && day <= last {
+            days.insert(day);
+            day = day + Days::new(1);
+        }
+    }
+
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// A calendar with the given `VEVENT` properties, one event per entry.
+    fn calendar(test: &str, events: &[&str]) -> (TempDir, ics::Calendar) {
+        let dir = TempDir::new(&format!("calendar-{test}"));
+        let events: String = events
+            .iter()
+            .map(|event| format!("BEGIN:VEVENT\r\nSUMMARY:Event\r\n{event}END:VEVENT\r\n"))
+            .collect();
+
+        let path = dir.join("calendar.ics");
+        std::fs::write(
+            &path,
+            format!("BEGIN:VCALENDAR\r\n{events}END:VCALENDAR\r\n"),
+        )
+        .unwrap();
+        let calendar = ics::Calendar::load(&[path]);
+
+        (dir, calendar)
+    }
+
+    #[test]
+    fn grid_starts_on_the_configured_weekday() {
+        // March 2024 starts on a Friday.
+        let first_of_month = date(2024, 3, 1);
+
+        assert_eq!(
+            grid_start(first_of_month, WeekStart::Monday),
+            date(2024, 2, 26)
+        );
+        assert_eq!(
+            grid_start(first_of_month, WeekStart::Sunday),
+            date(2024, 2, 25)
+        );
+        // Months starting on the first weekday don't show the previous week.
+        assert_eq!(
+            grid_start(date(2024, 4, 1), WeekStart::Monday),
+            date(2024, 4, 1)
+        );
+    }
+
+    #[test]
+    fn events_ending_at_midnight_mark_a_single_day() {
+        let (_dir, calendar) = calendar(
+            "midnight",
+            &[
+                "DTSTART:20240305T220000\r\nDTEND:20240306T000000\r\n",
+                "DTSTART;VALUE=DATE:20240310\r\nDTEND;VALUE=DATE:20240311\r\n",
+            ],
+        );
+
+        let days = days_with_events(&calendar, date(2024, 2, 26), date(2024, 4, 7));
+
+        assert_eq!(days, HashSet::from([date(2024, 3, 5), date(2024, 3, 10)]));
+    }
+
+    #[test]
+    fn multi_day_events_are_cut_at_the_edges_of_the_grid() {
+        let (_dir, calendar) = calendar(
+            "edges",
+            &[
+                "DTSTART;VALUE=DATE:20240224\r\nDTEND;VALUE=DATE:20240227\r\n",
+                "DTSTART:20240406T120000\r\nDTEND:20240409T120000\r\n",
+            ],
+        );
+
+        let days = days_with_events(&calendar, date(2024, 2, 26), date(2024, 4, 7));
+
+        assert_eq!(
+            days,
+            HashSet::from([date(2024, 2, 26), date(2024, 4, 6), date(2024, 4, 7)])
+        );
+    }
+}
@@ -3,15 +3,15 @@ use std::time::Duration;
 
 use spin::RwLock;
 use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
 use winit::event_loop::EventLoop;
-use winit::window::Window;
 
 use crate::config::ClockConfig;
 use crate::config::TextConfig;
-use crate::render::DrawError;
-use crate::render::Drawable;
 
 use super::text::Text;
+use super::Position;
+use super::Widget;
 use super::WidgetError;
 
 pub struct Clock {
@@ -21,11 +21,8 @@ pub struct Clock {
 
 impl Clock {
     pub fn new(event_loop: &EventLoop<()>, config: ClockConfig) -> Result<Self, WidgetError> {
-        let text_config = TextConfig {
-            text: get_time(config.show_seconds),
-            position: config.position,
-            font: config.font,
-        };
+        let text_config =
+            TextConfig::new(get_time(config.show_seconds), config.position, config.font);
         let widget = Text::new(text_config)?;
 
         let current_time = Arc::new(RwLock::new(get_time(config.show_seconds)));
@@ -50,11 +47,19 @@ impl Clock {
     }
 }
 
-impl Drawable for Clock {
-    fn draw(&mut self, window: &Window, buffer: &mut Pixmap) -> Result<(), DrawError> {
+impl Widget for Clock {
+    fn position(&self) -> &Position {
+        self.text_widget.position()
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
         self.text_widget
             .update_data(self.current_time.read().to_string());
-        self.text_widget.draw(window, buffer)
+        self.text_widget.measure(window, space)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.text_widget.draw_at(window, buffer, origin);
     }
 }
 
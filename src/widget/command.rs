@@ -0,0 +1,245 @@
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::process::Child;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use cosmic_text::Color;
+use spin::RwLock;
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::CommandConfig;
+use crate::config::TextConfig;
+
+use super::last_lines;
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// How often a running command is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone)]
+pub(super) enum CommandOutput {
+    Pending,
+    Success(String),
+    Failure(String),
+}
+
+pub struct Command {
+    text_widget: Text,
+    text_color: Color,
+    error_color: Color,
+    output: Arc<RwLock<CommandOutput>>,
+}
+
+impl Command {
+    pub fn new(event_loop: &EventLoop<()>, config: CommandConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.font.color;
+        let text_color = Color::rgba(r, g, b, a);
+        let (r, g, b, a) = config.error_color;
+        let error_color = Color::rgba(r, g, b, a);
+
+        let text_config = TextConfig::new(String::new(), config.position, config.font);
+        let widget = Text::new(text_config)?;
+
+        let output = Arc::new(RwLock::new(CommandOutput::Pending));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let output = output.clone();
+            let command = config.command;
+            let timeout = Duration::from_secs(config.timeout);
+            let lines = config.lines;
+            let interval = config.interval.map(Duration::from_secs);
+
+            std::thread::spawn(move || loop {
+                *output.write() = run(&command, timeout, lines);
+                event_loop_proxy.send_event(()).unwrap();
+
+                match interval {
+                    Some(interval) => std::thread::sleep(interval),
+                    None => break,
+                }
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            text_color,
+            error_color,
+            output,
+        })
+    }
+}
+
+impl Widget for Command {
+    fn position(&self) -> &Position {
+        self.text_widget.position()
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let (text, color) = match &*self.output.read() {
+            CommandOutput::Pending => (String::new(), self.text_color),
+            CommandOutput::Success(stdout) => (stdout.clone(), self.text_color),
+            CommandOutput::Failure(error) => (error.clone(), self.error_color),
+        };
+
+        self.text_widget.update_data(text);
+        self.text_widget.set_color(color);
+        self.text_widget.measure(window, space)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.text_widget.draw_at(window, buffer, origin);
+    }
+}
+
+/// Runs the command to completion or until the timeout elapses.
+pub(super) fn run(command: &str, timeout: Duration, lines: Option<usize>) -> CommandOutput {
+    let child = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // Its own process group, so a timeout also stops whatever the command started.
+        .process_group(0)
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => return CommandOutput::Failure(format!("could not run command: {err}")),
+    };
+
+    // Pipes are drained on their own threads so a chatty command can't fill them up and block
+    // while we wait for it to exit.
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = match wait(&mut child, timeout) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            // The drain threads are left behind, they end once the last process holding the
+            // pipes is gone.
+            unsafe { libc::kill(-(child.id() as i32), libc::SIGKILL) };
+            let _ = child.wait();
+            return CommandOutput::Failure(format!("timed out after {}s", timeout.as_secs()));
+        }
+        Err(err) => return CommandOutput::Failure(format!("could not wait for command: {err}")),
+    };
+
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let code = match status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => "terminated by signal".to_string(),
+        };
+
+        return match stderr.trim() {
+            "" => CommandOutput::Failure(code),
+            stderr => CommandOutput::Failure(format!("{}\n{code}", last_lines(stderr, lines))),
+        };
+    }
+
+    CommandOutput::Success(last_lines(stdout.trim(), lines))
+}
+
+fn wait(child: &mut Child, timeout: Duration) -> std::io::Result<Option<std::process::ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+
+        String::from_utf8_lossy(&output).into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Whether the process is gone, a zombie waiting to be reaped counts as gone.
+    fn exited(pid: &str) -> bool {
+        match std::fs::read_to_string(Path::new("/proc").join(pid).join("stat")) {
+            Ok(stat) => stat
+                .rsplit(')')
+                .next()
+                .unwrap()
+                .trim_start()
+                .starts_with('Z'),
+            Err(_) => true,
+        }
+    }
+
+    #[test]
+    fn output_keeps_the_last_lines() {
+        let output = run("printf 'one\\ntwo\\nthree\\n'", TIMEOUT, Some(2));
+
+        assert!(matches!(output, CommandOutput::Success(stdout) if stdout == "two\nthree"));
+    }
+
+    #[test]
+    fn failure_reports_stderr_and_exit_code() {
+        let output = run("echo out; echo oops >&2; exit 3", TIMEOUT, None);
+
+        assert!(matches!(output, CommandOutput::Failure(error) if error == "oops\nexit code 3"));
+
+        let output = run("exit 1", TIMEOUT, None);
+
+        assert!(matches!(output, CommandOutput::Failure(error) if error == "exit code 1"));
+    }
+
+    #[test]
+    fn timeout_stops_the_command_and_what_it_started() {
+        let dir = TempDir::new("command-timeout");
+        let pid_file = dir.join("pid");
+        let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let started = Instant::now();
+        let output = run(&command, Duration::from_secs(1), None);
+
+        assert!(matches!(output, CommandOutput::Failure(error) if error == "timed out after 1s"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !exited(pid.trim()) && Instant::now() < deadline {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        assert!(exited(pid.trim()));
+    }
+}
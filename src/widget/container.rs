@@ -0,0 +1,508 @@
+use std::time::Instant;
+
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalPosition;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoop;
+use winit::keyboard::Key;
+
+use crate::config;
+use crate::config::ContainerConfig;
+use crate::config::GridConfig;
+use crate::config::StackConfig;
+use crate::layout::Align;
+use crate::layout::Anchor;
+
+use super::from_config;
+use super::is_media_key;
+use super::Bounds;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// How a container places its children.
+enum Layout {
+    Row {
+        spacing: f32,
+        align: Align,
+    },
+    Column {
+        spacing: f32,
+        align: Align,
+    },
+    Stack {
+        align: Anchor,
+    },
+    Grid {
+        columns: usize,
+        column_spacing: f32,
+        row_spacing: f32,
+        align: Anchor,
+    },
+}
+
+/// Lays out other widgets next to or on top of each other, sized after what they measure.
+pub struct Container {
+    layout: Layout,
+    children: Vec<Box<dyn Widget>>,
+    position: Position,
+    /// Size of each child and where it goes in the container, as last measured.
+    sizes: Vec<Option<(f32, f32)>>,
+    places: Vec<(f32, f32)>,
+    /// Area each child covered in the window on the last frame.
+    areas: Vec<Option<Bounds>>,
+    cursor_position: Option<PhysicalPosition<f64>>,
+    focused: Option<usize>,
+}
+
+impl Container {
+    pub fn row(event_loop: &EventLoop<()>, config: ContainerConfig) -> Result<Self, WidgetError> {
+        let layout = Layout::Row {
+            spacing: config.spacing,
+            align: config.align,
+        };
+
+        Self::new(event_loop, layout, config.children, config.position)
+    }
+
+    pub fn column(
+        event_loop: &EventLoop<()>,
+        config: ContainerConfig,
+    ) -> Result<Self, WidgetError> {
+        let layout = Layout::Column {
+            spacing: config.spacing,
+            align: config.align,
+        };
+
+        Self::new(event_loop, layout, config.children, config.position)
+    }
+
+    pub fn stack(event_loop: &EventLoop<()>, config: StackConfig) -> Result<Self, WidgetError> {
+        let layout = Layout::Stack {
+            align: config.align,
+        };
+
+        Self::new(event_loop, layout, config.children, config.position)
+    }
+
+    pub fn grid(event_loop: &EventLoop<()>, config: GridConfig) -> Result<Self, WidgetError> {
+        let layout = Layout::Grid {
+            columns: config.columns.max(1),
+            column_spacing: config.column_spacing,
+            row_spacing: config.row_spacing,
+            align: config.align,
+        };
+
+        Self::new(event_loop, layout, config.children, config.position)
+    }
+
+    fn new(
+        event_loop: &EventLoop<()>,
+        layout: Layout,
+        children: Vec<config::Widget>,
+        position: Position,
+    ) -> Result<Self, WidgetError> {
+        let children = children
+            .into_iter()
+            .map(|child| from_config(event_loop, child))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::with_children(layout, children, position))
+    }
+
+    fn with_children(layout: Layout, children: Vec<Box<dyn Widget>>, position: Position) -> Self {
+        let focused = children.iter().position(|child| child.focusable());
+
+        Self {
+            layout,
+            sizes: vec![None; children.len()],
+            places: vec![(0.0, 0.0); children.len()],
+            areas: vec![None; children.len()],
+            children,
+            position,
+            cursor_position: None,
+            focused,
+        }
+    }
+
+    /// Index of the topmost child drawn under the given position.
+    fn child_at(&self, position: PhysicalPosition<f64>) -> Option<usize> {
+        self.areas.iter().rposition(|area| {
+            area.is_some_and(|area| area.contains(position.x as f32, position.y as f32))
+        })
+    }
+
+    /// Children a key goes to, the focused one or every child for media keys.
+    fn key_receivers(&self, key: &Key) -> Vec<usize> {
+        match is_media_key(key) {
+            true => (0..self.children.len()).collect(),
+            false => self.focused.into_iter().collect(),
+        }
+    }
+
+    fn forward(&mut self, index: usize, event: &WindowEvent) -> bool {
+        self.children[index].on_event(event)
+    }
+}
+
+impl Layout {
+    /// Computes the size of the container and where each child goes in it, from the size of
+    /// every child or `None` for children with nothing to draw.
+    fn arrange(&self, sizes: &[Option<(f32, f32)>]) -> ((f32, f32), Vec<(f32, f32)>) {
+        match *self {
+            Layout::Row { spacing, align } => {
+                let (length, thickness, places) = flow(sizes.iter().copied(), spacing);
+                let places = places
+                    .into_iter()
+                    .zip(sizes)
+                    .map(|(x, size)| {
+                        let height = size.map(|(_, height)| height).unwrap_or_default();
+                        (x, align.fraction() * (thickness - height))
+                    })
+                    .collect();
+
+                ((length, thickness), places)
+            }
+
+            Layout::Column { spacing, align } => {
+                let transposed = sizes.iter().map(|size| size.map(|(w, h)| (h, w)));
+                let (length, thickness, places) = flow(transposed, spacing);
+                let places = places
+                    .into_iter()
+                    .zip(sizes)
+                    .map(|(y, size)| {
+                        let width = size.map(|(width, _)| width).unwrap_or_default();
+                        (align.fraction() * (thickness - width), y)
+                    })
+                    .collect();
+
+                ((thickness, length), places)
+            }
+
+            Layout::Stack { align } => {
+                let (width, height) = sizes.iter().flatten().fold((0.0f32, 0.0f32), |max, size| {
+                    (max.0.max(size.0), max.1.max(size.1))
+                });
+                let (anchor_x, anchor_y) = align.fractions();
+                let places = sizes
+                    .iter()
+                    .map(|size| {
+                        let (w, h) = size.unwrap_or_default();
+                        (anchor_x * (width - w), anchor_y * (height - h))
+                    })
+                    .collect();
+
+                ((width, height), places)
+            }
+
+            Layout::Grid {
+                columns,
+                column_spacing,
+                row_spacing,
+                align,
+            } => {
+                let rows = sizes.len().div_ceil(columns);
+                let mut widths = vec![0.0f32; columns];
+                let mut heights = vec![0.0f32; rows];
+
+                for (index, (width, height)) in sizes
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, size)| size.map(|size| (index, size)))
+                {
+                    widths[index % columns] = widths[index % columns].max(width);
+                    heights[index / columns] = heights[index / columns].max(height);
+                }
+
+                let lefts = starts(&widths, column_spacing);
+                let tops = starts(&heights, row_spacing);
+                let (anchor_x, anchor_y) = align.fractions();
+
+                let places = sizes
+                    .iter()
+                    .enumerate()
+                    .map(|(index, size)| {
+                        let (column, row) = (index % columns, index / columns);
+                        let (w, h) = size.unwrap_or_default();
+
+                        (
+                            lefts[column] + anchor_x * (widths[column] - w),
+                            tops[row] + anchor_y * (heights[row] - h),
+                        )
+                    })
+                    .collect();
+
+                let width =
+                    widths.iter().sum::<f32>() + column_spacing * columns.saturating_sub(1) as f32;
+                let height =
+                    heights.iter().sum::<f32>() + row_spacing * rows.saturating_sub(1) as f32;
+
+                ((width, height), places)
+            }
+        }
+    }
+}
+
+/// Puts boxes one after the other along their first dimension, returning the total length, the
+/// largest second dimension and where each box starts. Boxes that are missing take no space.
+fn flow(sizes: impl Iterator<Item = Option<(f32, f32)>>, spacing: f32) -> (f32, f32, Vec<f32>) {
+    let mut length: Option<f32> = None;
+    let mut thickness = 0.0f32;
+    let mut places = Vec::new();
+
+    for size in sizes {
+        let start = length.map(|length| length + spacing).unwrap_or_default();
+        places.push(start);
+
+        if let Some((along, across)) = size {
+            length = Some(start + along);
+            thickness = thickness.max(across);
+        }
+    }
+
+    (length.unwrap_or_default(), thickness, places)
+}
+
+/// Where each row or column of a grid starts.
+fn starts(lengths: &[f32], spacing: f32) -> Vec<f32> {
+    lengths
+        .iter()
+        .scan(0.0, |start, length| {
+            let current = *start;
+            *start += length + spacing;
+            Some(current)
+        })
+        .collect()
+}
+
+impl Widget for Container {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        // Children can take as much room as the container has.
+        self.sizes = self
+            .children
+            .iter_mut()
+            .map(|child| child.measure(window, space))
+            .collect();
+
+        if self.sizes.iter().all(Option::is_none) {
+            self.areas.fill(None);
+            return None;
+        }
+
+        let (size, places) = self.layout.arrange(&self.sizes);
+        self.places = places;
+
+        Some(size)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        for (index, child) in self.children.iter_mut().enumerate() {
+            let (left, top) = self.places[index];
+
+            self.areas[index] = self.sizes[index].map(|size| {
+                // Placed on whole pixels so children are drawn as sharp as on their own.
+                let origin = (x + left.round() as i32, y + top.round() as i32);
+                child.draw_at(window, buffer, origin);
+
+                Bounds::new(origin, size)
+            });
+        }
+    }
+
+    fn on_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some(*position);
+
+                self.child_at(*position)
+                    .is_some_and(|index| self.forward(index, event))
+            }
+
+            WindowEvent::MouseInput { state, .. } => {
+                let Some(index) = self
+                    .cursor_position
+                    .and_then(|position| self.child_at(position))
+                else {
+                    return false;
+                };
+
+                if state.is_pressed() && self.children[index].focusable() {
+                    self.focused = Some(index);
+                }
+
+                self.forward(index, event)
+            }
+
+            WindowEvent::MouseWheel { .. } => self
+                .cursor_position
+                .and_then(|position| self.child_at(position))
+                .is_some_and(|index| self.forward(index, event)),
+
+            WindowEvent::KeyboardInput { event: key, .. } => {
+                let mut redraw = false;
+                for index in self.key_receivers(&key.logical_key) {
+                    redraw |= self.forward(index, event);
+                }
+                redraw
+            }
+
+            _ => {
+                // Every child sees the event, e.g. the window being hidden.
+                let mut redraw = false;
+                for index in 0..self.children.len() {
+                    redraw |= self.forward(index, event);
+                }
+                redraw
+            }
+        }
+    }
+
+    fn on_resize(&mut self, size: PhysicalSize<u32>) {
+        for child in self.children.iter_mut() {
+            child.on_resize(size);
+        }
+    }
+
+    fn on_background_change(&mut self) {
+        for child in self.children.iter_mut() {
+            child.on_background_change();
+        }
+    }
+
+    fn on_tick(&mut self, now: Instant) {
+        for child in self.children.iter_mut() {
+            child.on_tick(now);
+        }
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.children
+            .iter()
+            .filter_map(|child| child.next_tick())
+            .min()
+    }
+
+    fn focusable(&self) -> bool {
+        self.focused.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::keyboard::NamedKey;
+
+    use crate::config::Fill;
+    use crate::config::RectangleConfig;
+    use crate::widget::shape::Rectangle;
+
+    use super::*;
+
+    const WINDOW: PhysicalSize<u32> = PhysicalSize::new(200, 200);
+
+    fn rectangle(width: f32, height: f32) -> Box<dyn Widget> {
+        Box::new(Rectangle::new(RectangleConfig {
+            width,
+            height,
+            corner_radius: 0.0,
+            fill: Some(Fill::Color((255, 0, 0, 255))),
+            stroke: None,
+            position: Position::default(),
+        }))
+    }
+
+    #[test]
+    fn row_draws_children_once_at_their_place() {
+        let layout = Layout::Row {
+            spacing: 5.0,
+            align: Align::End,
+        };
+        let mut row = Container::with_children(
+            layout,
+            vec![rectangle(10.0, 10.0), rectangle(20.0, 30.0)],
+            Position::default(),
+        );
+        let mut buffer = Pixmap::new(WINDOW.width, WINDOW.height).unwrap();
+
+        assert_eq!(row.measure(WINDOW, (200.0, 200.0)), Some((35.0, 30.0)));
+        row.draw_at(WINDOW, &mut buffer, (100, 50));
+
+        assert_eq!(
+            row.areas,
+            [
+                Some(Bounds::new((100, 70), (10.0, 10.0))),
+                Some(Bounds::new((115, 50), (20.0, 30.0))),
+            ]
+        );
+
+        let painted = |x, y| buffer.pixel(x, y).unwrap().alpha() > 0;
+        assert!(painted(105, 75));
+        assert!(!painted(105, 55));
+        assert!(painted(120, 55));
+        assert!(!painted(100, 49));
+    }
+
+    #[test]
+    fn grid_sizes_columns_and_rows_after_their_largest_child() {
+        let layout = Layout::Grid {
+            columns: 2,
+            column_spacing: 4.0,
+            row_spacing: 2.0,
+            align: Anchor::TopLeft,
+        };
+        let sizes = [
+            Some((10.0, 5.0)),
+            Some((30.0, 8.0)),
+            None,
+            Some((6.0, 20.0)),
+        ];
+
+        let (size, places) = layout.arrange(&sizes);
+
+        assert_eq!(size, (44.0, 30.0));
+        assert_eq!(places, [(0.0, 0.0), (14.0, 0.0), (0.0, 10.0), (14.0, 10.0)]);
+    }
+
+    #[test]
+    fn children_with_nothing_to_draw_take_no_room() {
+        let layout = Layout::Column {
+            spacing: 10.0,
+            align: Align::Start,
+        };
+        let (size, places) = layout.arrange(&[None, Some((10.0, 10.0)), None, Some((5.0, 5.0))]);
+
+        assert_eq!(size, (10.0, 25.0));
+        assert_eq!(places[1], (0.0, 0.0));
+        assert_eq!(places[3], (0.0, 20.0));
+    }
+
+    #[test]
+    fn media_keys_reach_children_without_focus() {
+        let layout = Layout::Stack {
+            align: Anchor::Center,
+        };
+        let mut stack = Container::with_children(
+            layout,
+            vec![rectangle(10.0, 10.0), rectangle(20.0, 20.0)],
+            Position::default(),
+        );
+        stack.focused = Some(0);
+
+        assert_eq!(
+            stack.key_receivers(&Key::Named(NamedKey::MediaPlayPause)),
+            [0, 1]
+        );
+        assert_eq!(stack.key_receivers(&Key::Character("a".into())), [0]);
+
+        stack.focused = None;
+        assert_eq!(
+            stack.key_receivers(&Key::Named(NamedKey::MediaTrackNext)),
+            [0, 1]
+        );
+        assert!(stack.key_receivers(&Key::Named(NamedKey::Enter)).is_empty());
+    }
+}
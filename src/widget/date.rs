@@ -3,15 +3,15 @@ use std::time::Duration;
 
 use spin::RwLock;
 use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
 use winit::event_loop::EventLoop;
-use winit::window::Window;
 
 use crate::config::DateConfig;
 use crate::config::TextConfig;
-use crate::render::DrawError;
-use crate::render::Drawable;
 
 use super::text::Text;
+use super::Position;
+use super::Widget;
 use super::WidgetError;
 
 pub struct Date {
@@ -21,11 +21,7 @@ pub struct Date {
 
 impl Date {
     pub fn new(event_loop: &EventLoop<()>, config: DateConfig) -> Result<Self, WidgetError> {
-        let text_config = TextConfig {
-            text: get_date(&config.format),
-            position: config.position,
-            font: config.font,
-        };
+        let text_config = TextConfig::new(get_date(&config.format), config.position, config.font);
         let widget = Text::new(text_config)?;
 
         let current_time = Arc::new(RwLock::new(get_date(&config.format)));
@@ -49,11 +45,19 @@ impl Date {
     }
 }
 
-impl Drawable for Date {
-    fn draw(&mut self, window: &Window, buffer: &mut Pixmap) -> Result<(), DrawError> {
+impl Widget for Date {
+    fn position(&self) -> &Position {
+        self.text_widget.position()
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
         self.text_widget
             .update_data(self.current_time.read().to_string());
-        self.text_widget.draw(window, buffer)
+        self.text_widget.measure(window, space)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.text_widget.draw_at(window, buffer, origin);
     }
 }
 
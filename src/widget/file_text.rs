@@ -0,0 +1,269 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::fs::Metadata;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use spin::RwLock;
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+use winit::event_loop::EventLoopProxy;
+
+use crate::config::FileSource;
+use crate::config::FileTextConfig;
+use crate::config::TextConfig;
+
+use super::last_lines;
+use super::text::Text;
+use super::watch_files;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// Lines kept from a stream when no limit was configured, so it can't grow forever.
+const DEFAULT_STREAM_LINES: usize = 100;
+
+/// Shortest time between two openings of a FIFO.
+const FIFO_REOPEN_DELAY: Duration = Duration::from_millis(250);
+
+pub struct FileText {
+    text_widget: Text,
+    contents: Arc<RwLock<String>>,
+}
+
+impl FileText {
+    pub fn new(event_loop: &EventLoop<()>, config: FileTextConfig) -> Result<Self, WidgetError> {
+        let text_config = TextConfig::new(String::new(), config.position, config.font);
+        let widget = Text::new(text_config)?;
+
+        let contents = Arc::new(RwLock::new(String::new()));
+        let event_loop_proxy = event_loop.create_proxy();
+
+        match config.source {
+            FileSource::File(path) => watch_file(
+                PathBuf::from(path),
+                config.lines,
+                contents.clone(),
+                event_loop_proxy,
+            )?,
+
+            FileSource::Fifo(path) => {
+                // Reading a regular file would return EOF right away and reopen it forever.
+                if !is_fifo(std::fs::metadata(&path)) {
+                    return Err(WidgetError);
+                }
+
+                let contents = contents.clone();
+                let lines = config.lines.unwrap_or(DEFAULT_STREAM_LINES);
+
+                std::thread::spawn(move || {
+                    follow_fifo(Path::new(&path), lines, &contents, || {
+                        event_loop_proxy.send_event(()).unwrap();
+                    });
+                });
+            }
+
+            FileSource::Stdin => {
+                let contents = contents.clone();
+                let lines = config.lines.unwrap_or(DEFAULT_STREAM_LINES);
+
+                std::thread::spawn(move || {
+                    let mut buffer = VecDeque::with_capacity(lines);
+
+                    stream_lines(
+                        std::io::stdin().lock(),
+                        lines,
+                        &mut buffer,
+                        &contents,
+                        || {
+                            event_loop_proxy.send_event(()).unwrap();
+                        },
+                    );
+                });
+            }
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            contents,
+        })
+    }
+}
+
+impl Widget for FileText {
+    fn position(&self) -> &Position {
+        self.text_widget.position()
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        self.text_widget
+            .update_data(self.contents.read().to_string());
+        self.text_widget.measure(window, space)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.text_widget.draw_at(window, buffer, origin);
+    }
+}
+
+/// Reads the file now and again every time it changes on disk.
+fn watch_file(
+    path: PathBuf,
+    lines: Option<usize>,
+    contents: Arc<RwLock<String>>,
+    event_loop_proxy: EventLoopProxy<()>,
+) -> Result<(), WidgetError> {
+    *contents.write() = read_file(&path, lines);
+
+    watch_files(vec![path.clone()], move || {
+        *contents.write() = read_file(&path, lines);
+        event_loop_proxy.send_event(()).unwrap();
+    })
+}
+
+fn is_fifo(metadata: std::io::Result<Metadata>) -> bool {
+    metadata.is_ok_and(|metadata| metadata.file_type().is_fifo())
+}
+
+fn read_file(path: &Path, lines: Option<usize>) -> String {
+    let contents = std::fs::read_to_string(path).unwrap_or_default();
+
+    last_lines(contents.trim_end(), lines)
+}
+
+/// Streams the lines written to the FIFO at `path` by one writer after the other, until it is
+/// gone or replaced by something else.
+fn follow_fifo(path: &Path, lines: usize, contents: &RwLock<String>, on_change: impl Fn()) {
+    let mut buffer = VecDeque::with_capacity(lines);
+
+    // Opening a FIFO blocks until a writer shows up, and reading returns EOF once every writer
+    // went away, so keep reopening it to follow the next writer.
+    loop {
+        let Ok(fifo) = File::open(path) else {
+            break;
+        };
+        if !is_fifo(fifo.metadata()) {
+            break;
+        }
+
+        let opened = Instant::now();
+        stream_lines(
+            BufReader::new(fifo),
+            lines,
+            &mut buffer,
+            contents,
+            &on_change,
+        );
+
+        // Writers that keep connecting and leaving would make this spin.
+        if opened.elapsed() < FIFO_REOPEN_DELAY {
+            std::thread::sleep(FIFO_REOPEN_DELAY);
+        }
+    }
+}
+
+/// Appends every line read to `buffer`, keeping only the last `lines` of them.
+fn stream_lines<R: BufRead>(
+    reader: R,
+    lines: usize,
+    buffer: &mut VecDeque<String>,
+    contents: &RwLock<String>,
+    on_change: impl Fn(),
+) {
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        buffer.push_back(line);
+        while buffer.len() > lines {
+            buffer.pop_front();
+        }
+
+        *contents.write() = buffer
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        on_change();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::sync::mpsc;
+
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn files_keep_their_last_lines_as_they_grow() {
+        let directory = TempDir::new("file-text-grow");
+        let path = directory.join("log");
+        std::fs::write(&path, "one\ntwo\nthree\n").unwrap();
+
+        assert_eq!(read_file(&path, Some(2)), "two\nthree");
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "four").unwrap();
+
+        assert_eq!(read_file(&path, Some(2)), "three\nfour");
+        assert_eq!(read_file(&path, None), "one\ntwo\nthree\nfour");
+        assert_eq!(read_file(&directory.join("missing"), Some(2)), "");
+    }
+
+    #[test]
+    fn fifo_is_reopened_for_the_next_writer() {
+        let directory = TempDir::new("file-text-fifo");
+        let fifo = directory.join("fifo");
+        let created = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(created.success());
+
+        let contents = Arc::new(RwLock::new(String::new()));
+        let (changed, changes) = mpsc::channel();
+        let reader = {
+            let contents = contents.clone();
+            let fifo = fifo.clone();
+
+            std::thread::spawn(move || {
+                follow_fifo(&fifo, 2, &contents, || changed.send(()).unwrap());
+            })
+        };
+
+        let wait_for = |expected: &str| {
+            while *contents.read() != expected {
+                changes.recv_timeout(Duration::from_secs(5)).unwrap();
+            }
+        };
+
+        let mut writer = OpenOptions::new().write(true).open(&fifo).unwrap();
+        writer.write_all(b"one\ntwo\nthree\n").unwrap();
+        drop(writer);
+        wait_for("two\nthree");
+
+        // The second writer is read from once the first one closed the FIFO. Replacing the FIFO
+        // by a regular file before closing it stops the reader.
+        let mut writer = OpenOptions::new().write(true).open(&fifo).unwrap();
+        writer.write_all(b"four\n").unwrap();
+        let file = directory.join("file");
+        std::fs::write(&file, "not a pipe").unwrap();
+        std::fs::rename(&file, &fifo).unwrap();
+        drop(writer);
+        wait_for("three\nfour");
+
+        reader.join().unwrap();
+    }
+}
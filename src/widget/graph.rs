@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use spin::RwLock;
+use tiny_skia::Color;
+use tiny_skia::FillRule;
+use tiny_skia::LineCap;
+use tiny_skia::LineJoin;
+use tiny_skia::Paint;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use tiny_skia::Stroke;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::GraphConfig;
+use crate::config::GraphSource;
+use crate::config::GraphStyle;
+
+use super::command;
+use super::command::CommandOutput;
+use super::system::Metric;
+use super::system::Sampler;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// How long a command feeding a graph may run before it is killed.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Fixed size ring buffer of samples, oldest first.
+#[derive(Debug, Clone)]
+pub(super) struct History {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl History {
+    pub(super) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub(super) fn push(&mut self, sample: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    pub(super) fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Largest sample kept, if any.
+    pub(super) fn max(&self) -> Option<f32> {
+        self.iter().reduce(f32::max)
+    }
+
+    fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples.iter().copied()
+    }
+}
+
+/// How to draw a [`History`].
+#[derive(Debug, Clone)]
+pub(super) struct Chart {
+    pub(super) style: GraphStyle,
+    pub(super) color: Color,
+    pub(super) fill_color: Color,
+    pub(super) line_width: f32,
+    pub(super) smooth: bool,
+    pub(super) min: Option<f32>,
+    pub(super) max: Option<f32>,
+}
+
+impl Chart {
+    /// Draws the samples inside `area`, the newest on the right edge.
+    pub(super) fn draw(&self, buffer: &mut Pixmap, area: Rect, history: &History) {
+        if history.len() == 0 {
+            return;
+        }
+
+        let (step, points) = self.points(area, history);
+
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Default::default()
+        };
+
+        match self.style {
+            GraphStyle::Bar => {
+                paint.set_color(self.color);
+
+                for (x, y) in points {
+                    let bar = Rect::from_ltrb(x + step * 0.1, y, x + step * 0.9, area.bottom());
+                    if let Some(bar) = bar {
+                        buffer.fill_rect(bar, &paint, Transform::identity(), None);
+                    }
+                }
+            }
+
+            GraphStyle::Line | GraphStyle::Area => {
+                if points.len() < 2 {
+                    return;
+                }
+
+                if let GraphStyle::Area = self.style {
+                    let mut path = self.line_path(&points);
+                    path.line_to(points[points.len() - 1].0, area.bottom());
+                    path.line_to(points[0].0, area.bottom());
+                    path.close();
+
+                    if let Some(path) = path.finish() {
+                        paint.set_color(self.fill_color);
+                        buffer.fill_path(
+                            &path,
+                            &paint,
+                            FillRule::Winding,
+                            Transform::identity(),
+                            None,
+                        );
+                    }
+                }
+
+                let Some(path) = self.line_path(&points).finish() else {
+                    return;
+                };
+
+                let stroke = Stroke {
+                    width: self.line_width,
+                    line_cap: LineCap::Round,
+                    line_join: LineJoin::Round,
+                    ..Default::default()
+                };
+
+                paint.set_color(self.color);
+                buffer.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+            }
+        }
+    }
+
+    /// Where each sample lands inside `area`, along with the horizontal distance between two
+    /// samples. Bars start at their point and take up the whole step.
+    fn points(&self, area: Rect, history: &History) -> (f32, Vec<(f32, f32)>) {
+        let (min, max) = self.range(history);
+
+        // Samples fill the chart from the right, so it scrolls left as the history fills up.
+        let slots = history.capacity().max(2);
+        let step = match self.style {
+            GraphStyle::Bar => area.width() / slots as f32,
+            GraphStyle::Line | GraphStyle::Area => area.width() / (slots - 1) as f32,
+        };
+
+        let points = history
+            .iter()
+            .enumerate()
+            .map(|(index, sample)| {
+                let slot = slots - history.len() + index;
+                let value = ((sample - min) / (max - min)).clamp(0.0, 1.0);
+
+                (
+                    area.x() + step * slot as f32,
+                    area.bottom() - area.height() * value,
+                )
+            })
+            .collect();
+
+        (step, points)
+    }
+
+    /// Vertical axis range, falling back to the range of the samples. Swapped bounds are put
+    /// back in order.
+    fn range(&self, history: &History) -> (f32, f32) {
+        let min = self
+            .min
+            .unwrap_or_else(|| history.iter().fold(f32::INFINITY, f32::min));
+        let max = self
+            .max
+            .unwrap_or_else(|| history.iter().fold(f32::NEG_INFINITY, f32::max));
+
+        let (min, max) = (min.min(max), min.max(max));
+
+        // A flat line would divide by zero, so give it some room to sit in the middle.
+        match max - min > f32::EPSILON {
+            true => (min, max),
+            false => (min - 1.0, max + 1.0),
+        }
+    }
+
+    fn line_path(&self, points: &[(f32, f32)]) -> PathBuilder {
+        let mut path = PathBuilder::new();
+        path.move_to(points[0].0, points[0].1);
+
+        if !self.smooth {
+            for &(x, y) in &points[1..] {
+                path.line_to(x, y);
+            }
+
+            return path;
+        }
+
+        // Catmull-Rom spline through the samples, converted to cubic bézier segments.
+        for index in 0..points.len() - 1 {
+            let p0 = points[index.saturating_sub(1)];
+            let p1 = points[index];
+            let p2 = points[index + 1];
+            let p3 = points[(index + 2).min(points.len() - 1)];
+
+            path.cubic_to(
+                p1.0 + (p2.0 - p0.0) / 6.0,
+                p1.1 + (p2.1 - p0.1) / 6.0,
+                p2.0 - (p3.0 - p1.0) / 6.0,
+                p2.1 - (p3.1 - p1.1) / 6.0,
+                p2.0,
+                p2.1,
+            );
+        }
+
+        path
+    }
+}
+
+pub struct Graph {
+    position: Position,
+    width: f32,
+    height: f32,
+    chart: Chart,
+    history: Arc<RwLock<History>>,
+}
+
+impl Graph {
+    pub fn new(event_loop: &EventLoop<()>, config: GraphConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.color;
+        let color = Color::from_rgba8(r, g, b, a);
+        let fill_color = match config.fill_color {
+            Some((r, g, b, a)) => Color::from_rgba8(r, g, b, a),
+            None => {
+                let mut fill_color = color;
+                fill_color.set_alpha(color.alpha() * 0.3);
+                fill_color
+            }
+        };
+
+        let history = Arc::new(RwLock::new(History::new(config.samples)));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let history = history.clone();
+            let interval = Duration::from_secs(config.interval.max(1));
+            let mut source = SampleSource::new(config.source);
+
+            std::thread::spawn(move || loop {
+                if let Some(sample) = source.sample() {
+                    history.write().push(sample);
+                    event_loop_proxy.send_event(()).unwrap();
+                }
+
+                std::thread::sleep(interval);
+            });
+        }
+
+        Ok(Self {
+            position: config.position,
+            width: config.width,
+            height: config.height,
+            chart: Chart {
+                style: config.style,
+                color,
+                fill_color,
+                line_width: config.line_width,
+                smooth: config.smooth,
+                min: config.min,
+                max: config.max,
+            },
+            history,
+        })
+    }
+}
+
+impl Widget for Graph {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, _space: (f32, f32)) -> Option<(f32, f32)> {
+        Some((self.width, self.height))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        if let Some(area) = Rect::from_xywh(x as f32, y as f32, self.width, self.height) {
+            self.chart.draw(buffer, area, &self.history.read());
+        }
+    }
+}
+
+enum SampleSource {
+    Command(String),
+    File(PathBuf),
+    Proc(Sampler),
+}
+
+impl SampleSource {
+    fn new(source: GraphSource) -> Self {
+        match source {
+            GraphSource::Command(command) => SampleSource::Command(command),
+            GraphSource::File(path) => SampleSource::File(PathBuf::from(path)),
+            GraphSource::Cpu(root) => SampleSource::Proc(Sampler::new(Metric::Cpu, root.into())),
+            GraphSource::Memory(root) => {
+                SampleSource::Proc(Sampler::new(Metric::Memory, root.into()))
+            }
+            GraphSource::LoadAverage(root) => {
+                SampleSource::Proc(Sampler::new(Metric::LoadAverage, root.into()))
+            }
+        }
+    }
+
+    fn sample(&mut self) -> Option<f32> {
+        match self {
+            SampleSource::Command(command) => match command::run(command, COMMAND_TIMEOUT, Some(1))
+            {
+                CommandOutput::Success(output) => parse_sample(&output),
+                CommandOutput::Pending | CommandOutput::Failure(_) => None,
+            },
+
+            SampleSource::File(path) => parse_sample(&std::fs::read_to_string(path).ok()?),
+
+            SampleSource::Proc(sampler) => sampler.sample()?.fraction.map(|value| value * 100.0),
+        }
+    }
+}
+
+/// Parses the first word of the text as a number, so values followed by units still work.
+fn parse_sample(text: &str) -> Option<f32> {
+    text.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(style: GraphStyle, min: Option<f32>, max: Option<f32>) -> Chart {
+        Chart {
+            style,
+            color: Color::WHITE,
+            fill_color: Color::WHITE,
+            line_width: 1.0,
+            smooth: false,
+            min,
+            max,
+        }
+    }
+
+    fn history(capacity: usize, samples: &[f32]) -> History {
+        let mut history = History::new(capacity);
+        for &sample in samples {
+            history.push(sample);
+        }
+
+        history
+    }
+
+    #[test]
+    fn history_drops_the_oldest_sample_when_full() {
+        let history = history(3, &[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        assert_eq!(history.iter().collect::<Vec<_>>(), [3.0, 4.0, 5.0]);
+        assert_eq!(history.max(), Some(5.0));
+        // A history always keeps at least one sample.
+        assert_eq!(History::new(0).capacity(), 1);
+    }
+
+    #[test]
+    fn range_follows_the_samples_unless_given() {
+        let samples = history(4, &[2.0, 6.0, 4.0]);
+
+        assert_eq!(
+            chart(GraphStyle::Line, None, None).range(&samples),
+            (2.0, 6.0)
+        );
+        assert_eq!(
+            chart(GraphStyle::Line, Some(0.0), Some(100.0)).range(&samples),
+            (0.0, 100.0)
+        );
+        assert_eq!(
+            chart(GraphStyle::Line, Some(0.0), None).range(&samples),
+            (0.0, 6.0)
+        );
+    }
+
+    #[test]
+    fn flat_and_swapped_ranges_stay_usable() {
+        let flat = history(4, &[5.0, 5.0, 5.0]);
+
+        assert_eq!(chart(GraphStyle::Line, None, None).range(&flat), (4.0, 6.0));
+        assert_eq!(
+            chart(GraphStyle::Line, Some(3.0), Some(3.0)).range(&flat),
+            (2.0, 4.0)
+        );
+
+        let samples = history(4, &[2.0, 6.0]);
+        assert_eq!(
+            chart(GraphStyle::Line, Some(10.0), Some(0.0)).range(&samples),
+            (0.0, 10.0)
+        );
+
+        // Flat samples sit in the middle rather than dividing by zero.
+        let area = Rect::from_xywh(0.0, 0.0, 30.0, 20.0).unwrap();
+        let (_, points) = chart(GraphStyle::Line, None, None).points(area, &flat);
+        assert!(points.iter().all(|&(_, y)| y == 10.0));
+    }
+
+    #[test]
+    fn lines_and_areas_span_the_whole_width() {
+        let area = Rect::from_xywh(10.0, 5.0, 30.0, 20.0).unwrap();
+        let samples = history(4, &[0.0, 50.0, 100.0]);
+
+        for style in [GraphStyle::Line, GraphStyle::Area] {
+            let (step, points) = chart(style, Some(0.0), Some(100.0)).points(area, &samples);
+
+            // Four slots, the newest sample on the right edge and the first slot still empty.
+            assert_eq!(step, 10.0);
+            assert_eq!(points, [(20.0, 25.0), (30.0, 15.0), (40.0, 5.0)]);
+        }
+    }
+
+    #[test]
+    fn bars_take_one_slot_each() {
+        let area = Rect::from_xywh(10.0, 5.0, 40.0, 20.0).unwrap();
+        let samples = history(4, &[25.0, 150.0]);
+
+        let (step, points) = chart(GraphStyle::Bar, Some(0.0), Some(100.0)).points(area, &samples);
+
+        // Out of range samples are clamped to the top of the chart.
+        assert_eq!(step, 10.0);
+        assert_eq!(points, [(30.0, 20.0), (40.0, 5.0)]);
+    }
+}
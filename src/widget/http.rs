@@ -0,0 +1,351 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use cosmic_text::Color;
+use serde_json::Value;
+use spin::RwLock;
+use thiserror::Error;
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::HttpConfig;
+use crate::config::TextConfig;
+
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// How long a request may take before giving up.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+struct Response {
+    values: BTreeMap<String, String>,
+    received_at: Instant,
+}
+
+pub struct Http {
+    text_widget: Text,
+    text_color: Color,
+    stale_color: Color,
+    template: String,
+    stale_indicator: String,
+    stale_after: Duration,
+    response: Arc<RwLock<Option<Response>>>,
+}
+
+impl Http {
+    pub fn new(event_loop: &EventLoop<()>, config: HttpConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.font.color;
+        let text_color = Color::rgba(r, g, b, a);
+        let (r, g, b, a) = config.stale_color;
+        let stale_color = Color::rgba(r, g, b, a);
+
+        let text_config = TextConfig::new(String::new(), config.position, config.font);
+        let widget = Text::new(text_config)?;
+
+        let selectors = config
+            .values
+            .into_iter()
+            .map(|(name, selector)| Ok((name, Selector::parse(&selector)?)))
+            .collect::<Result<Vec<_>, WidgetError>>()?;
+
+        let response = Arc::new(RwLock::new(None));
+        let interval = Duration::from_secs(config.interval.max(1));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let response = response.clone();
+            let url = config.url;
+            let headers = config.headers;
+
+            std::thread::spawn(move || loop {
+                let request = headers
+                    .iter()
+                    .fold(ureq::get(&url), |request, (name, value)| {
+                        request.set(name, &expand_env(value, |name| std::env::var(name).ok()))
+                    });
+
+                match get(request).and_then(|body| Ok(serde_json::from_str::<Value>(&body)?)) {
+                    Ok(json) => {
+                        let values = selectors
+                            .iter()
+                            .filter_map(|(name, selector)| {
+                                let value = selector.select(&json)?;
+                                Some((name.clone(), value_to_string(value)))
+                            })
+                            .collect();
+
+                        *response.write() = Some(Response {
+                            values,
+                            received_at: Instant::now(),
+                        });
+                    }
+
+                    Err(err) => println!("Error fetching {url}: {err}"),
+                }
+
+                // Wake up even on failures so the stale indicator shows up in time.
+                event_loop_proxy.send_event(()).unwrap();
+                std::thread::sleep(interval);
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            text_color,
+            stale_color,
+            template: config.template,
+            stale_indicator: config.stale_indicator,
+            stale_after: config
+                .stale_after
+                .map(Duration::from_secs)
+                .unwrap_or(interval * 3),
+            response,
+        })
+    }
+}
+
+impl Widget for Http {
+    fn position(&self) -> &Position {
+        self.text_widget.position()
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let response = self.response.read().clone()?;
+
+        let mut text = render_template(&self.template, &response.values);
+        let mut color = self.text_color;
+
+        if response.received_at.elapsed() > self.stale_after {
+            text.push_str(&self.stale_indicator);
+            color = self.stale_color;
+        }
+
+        self.text_widget.update_data(text);
+        self.text_widget.set_color(color);
+        self.text_widget.measure(window, space)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.text_widget.draw_at(window, buffer, origin);
+    }
+}
+
+/// Sends the request and reads the whole response body.
+pub(super) fn get(request: ureq::Request) -> Result<String, FetchError> {
+    let body = request
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .map_err(Box::new)?
+        .into_string()?;
+
+    Ok(body)
+}
+
+#[derive(Debug, Error)]
+pub(super) enum FetchError {
+    #[error("the request failed: {0}")]
+    Request(#[from] Box<ureq::Error>),
+
+    #[error("could not read the response: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("the response is not valid: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(isize),
+}
+
+/// Path to a value inside a JSON document, like `$.builds[0].status`.
+#[derive(Debug, Clone)]
+struct Selector(Vec<Segment>);
+
+impl Selector {
+    fn parse(selector: &str) -> Result<Self, WidgetError> {
+        let selector = selector.strip_prefix('$').unwrap_or(selector);
+        let mut segments = Vec::new();
+
+        for part in selector.split('.').filter(|part| !part.is_empty()) {
+            // A part is a key optionally followed by indices, e.g. `builds[0][1]`.
+            let (key, mut indices) = match part.find('[') {
+                Some(start) => part.split_at(start),
+                None => (part, ""),
+            };
+
+            if !key.is_empty() {
+                segments.push(Segment::Key(key.to_string()));
+            }
+
+            while !indices.is_empty() {
+                let (index, rest) = indices
+                    .strip_prefix('[')
+                    .and_then(|indices| indices.split_once(']'))
+                    .ok_or(WidgetError)?;
+
+                segments.push(Segment::Index(
+                    index.trim().parse().map_err(|_| WidgetError)?,
+                ));
+                indices = rest;
+            }
+        }
+
+        Ok(Self(segments))
+    }
+
+    fn select<'a>(&self, json: &'a Value) -> Option<&'a Value> {
+        self.0
+            .iter()
+            .try_fold(json, |value, segment| match segment {
+                Segment::Key(key) => value.get(key),
+                Segment::Index(index) => {
+                    let array = value.as_array()?;
+                    let index = match *index < 0 {
+                        true => array.len().checked_sub(index.unsigned_abs())?,
+                        false => *index as usize,
+                    };
+
+                    array.get(index)
+                }
+            })
+    }
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// Replaces every `{name}` with its value, or `?` when the value couldn't be extracted.
+fn render_template(template: &str, values: &BTreeMap<String, String>) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+
+        text.push_str(&rest[..start]);
+
+        let name = &rest[start + 1..start + end];
+        text.push_str(values.get(name).map(String::as_str).unwrap_or("?"));
+
+        rest = &rest[start + end + 1..];
+    }
+
+    text.push_str(rest);
+    text
+}
+
+/// Replaces every `${NAME}` with the value `lookup` gives for `NAME`, usually the environment
+/// variable of that name. Unknown names are replaced by nothing.
+fn expand_env(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+
+        expanded.push_str(&rest[..start]);
+
+        let name = &rest[start + 2..start + end];
+        expanded.push_str(&lookup(name).unwrap_or_default());
+
+        rest = &rest[start + end + 1..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn selector_follows_nested_keys_and_indices() {
+        let json = json!({
+            "builds": [
+                { "status": "passed", "steps": [[1, 2], [3, 4]] },
+                { "status": "failed" },
+            ],
+            "count": 2,
+        });
+        let select = |selector: &str| Selector::parse(selector).unwrap().select(&json).cloned();
+
+        assert_eq!(select("$.builds[0].status"), Some(json!("passed")));
+        assert_eq!(select("builds[-1].status"), Some(json!("failed")));
+        assert_eq!(select("$.builds[0].steps[1][0]"), Some(json!(3)));
+        assert_eq!(select("$.count"), Some(json!(2)));
+        assert_eq!(select("$"), Some(json.clone()));
+    }
+
+    #[test]
+    fn selector_misses_return_nothing() {
+        let json = json!({ "builds": [{ "status": "passed" }] });
+        let select = |selector: &str| Selector::parse(selector).unwrap().select(&json).cloned();
+
+        assert_eq!(select("$.missing"), None);
+        assert_eq!(select("$.builds[0].missing"), None);
+        assert_eq!(select("$.builds[1]"), None);
+        assert_eq!(select("$.builds[-2]"), None);
+        assert_eq!(select("$.builds.status"), None);
+    }
+
+    #[test]
+    fn invalid_selectors_are_rejected() {
+        assert!(Selector::parse("$.builds[").is_err());
+        assert!(Selector::parse("$.builds[first]").is_err());
+        assert!(Selector::parse("$.builds[0").is_err());
+    }
+
+    #[test]
+    fn template_placeholders_without_a_value_show_a_question_mark() {
+        let values = BTreeMap::from([
+            ("status".to_string(), "passed".to_string()),
+            ("count".to_string(), "2".to_string()),
+        ]);
+
+        assert_eq!(
+            render_template("{status}: {count} of {total}", &values),
+            "passed: 2 of ?"
+        );
+        assert_eq!(
+            render_template("no placeholders", &values),
+            "no placeholders"
+        );
+        assert_eq!(render_template("open {status", &values), "open {status");
+        // Selected strings are shown without their quotes.
+        assert_eq!(value_to_string(&json!("text")), "text");
+        assert_eq!(value_to_string(&json!([1, 2])), "[1,2]");
+    }
+
+    #[test]
+    fn unset_environment_variables_expand_to_nothing() {
+        let environment = HashMap::from([("TOKEN", "secret")]);
+        let lookup = |name: &str| environment.get(name).map(ToString::to_string);
+
+        assert_eq!(expand_env("Bearer ${TOKEN}", lookup), "Bearer secret");
+        assert_eq!(expand_env("[${UNSET}]", lookup), "[]");
+        assert_eq!(expand_env("${TOKEN}-${TOKEN}", lookup), "secret-secret");
+        assert_eq!(expand_env("${TOKEN", lookup), "${TOKEN");
+    }
+}
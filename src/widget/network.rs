@@ -0,0 +1,374 @@
+use std::ffi::CStr;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use spin::RwLock;
+use tiny_skia::Color;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::GraphStyle;
+use crate::config::MeterConfig;
+use crate::config::NetworkConfig;
+use crate::config::TextConfig;
+
+use super::graph::Chart;
+use super::graph::History;
+use super::human_bytes;
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// Space between the text and the sparkline below it.
+const SPARKLINE_SPACING: f32 = 8.0;
+
+#[derive(Debug, Clone, Default)]
+struct InterfaceStatus {
+    state: String,
+    addresses: Vec<IpAddr>,
+    /// Bytes per second received since the previous sample.
+    rx_rate: Option<f64>,
+    /// Bytes per second transmitted since the previous sample.
+    tx_rate: Option<f64>,
+}
+
+#[derive(Debug)]
+struct NetworkState {
+    status: InterfaceStatus,
+    rx_history: History,
+    tx_history: History,
+}
+
+pub struct Network {
+    text_widget: Text,
+    position: Position,
+    interface: String,
+    format: String,
+    sparkline: Option<MeterConfig>,
+    state: Arc<RwLock<NetworkState>>,
+    /// Height of the text as last measured, the sparkline goes below it.
+    text_height: f32,
+}
+
+impl Network {
+    pub fn new(event_loop: &EventLoop<()>, config: NetworkConfig) -> Result<Self, WidgetError> {
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let samples = config
+            .sparkline
+            .as_ref()
+            .map(|sparkline| sparkline.samples)
+            .unwrap_or(1);
+
+        let state = Arc::new(RwLock::new(NetworkState {
+            status: InterfaceStatus::default(),
+            rx_history: History::new(samples),
+            tx_history: History::new(samples),
+        }));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let state = state.clone();
+            let interval = Duration::from_secs(config.interval.max(1));
+            let interface = config.interface.clone();
+            let proc_root = PathBuf::from(config.proc_root);
+            let sys_root = PathBuf::from(config.sys_root);
+
+            std::thread::spawn(move || {
+                let mut previous: Option<(Instant, u64, u64)> = None;
+
+                loop {
+                    let now = Instant::now();
+                    let counters = read_counters(&proc_root, &interface);
+
+                    let rates = previous.zip(counters).and_then(|(previous, counters)| {
+                        rates(previous, (now, counters.0, counters.1))
+                    });
+                    previous = counters.map(|(rx, tx)| (now, rx, tx));
+
+                    {
+                        let mut state = state.write();
+
+                        if let Some((rx, tx)) = rates {
+                            state.rx_history.push(rx as f32);
+                            state.tx_history.push(tx as f32);
+                        }
+
+                        state.status = InterfaceStatus {
+                            state: read_operstate(&sys_root, &interface),
+                            addresses: interface_addresses(&interface),
+                            rx_rate: rates.map(|(rx, _)| rx),
+                            tx_rate: rates.map(|(_, tx)| tx),
+                        };
+                    }
+
+                    event_loop_proxy.send_event(()).unwrap();
+                    std::thread::sleep(interval);
+                }
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            position: config.position,
+            interface: config.interface,
+            format: config.format,
+            sparkline: config.sparkline,
+            state,
+            text_height: 0.0,
+        })
+    }
+}
+
+impl Widget for Network {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let state = self.state.read();
+        let status = &state.status;
+
+        let rate = |rate: Option<f64>| match rate {
+            Some(rate) => format!("{}/s", human_bytes(rate)),
+            None => "-".to_string(),
+        };
+
+        let addresses = status
+            .addresses
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let text = self
+            .format
+            .replace("{interface}", &self.interface)
+            .replace("{state}", &status.state)
+            .replace("{address}", &addresses)
+            .replace("{rx}", &rate(status.rx_rate))
+            .replace("{tx}", &rate(status.tx_rate));
+        self.text_widget.update_data(text);
+
+        let (text_width, text_height) = self.text_widget.layout(space);
+        self.text_height = text_height;
+
+        match &self.sparkline {
+            Some(sparkline) => Some((
+                text_width.max(sparkline.width),
+                text_height + SPARKLINE_SPACING + sparkline.height,
+            )),
+            None => Some((text_width, text_height)),
+        }
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        self.text_widget.paint(buffer, x, y);
+
+        if let Some(sparkline) = &self.sparkline {
+            let area = Rect::from_xywh(
+                x as f32,
+                y as f32 + self.text_height + SPARKLINE_SPACING,
+                sparkline.width,
+                sparkline.height,
+            );
+
+            if let Some(area) = area {
+                let state = self.state.read();
+
+                // Both lines share the same scale so they can be compared.
+                let max = [state.rx_history.max(), state.tx_history.max()]
+                    .into_iter()
+                    .flatten()
+                    .fold(1.0, f32::max);
+
+                let (r, g, b, a) = sparkline.color;
+                let mut chart = Chart {
+                    style: GraphStyle::Line,
+                    color: Color::from_rgba8(r, g, b, a),
+                    fill_color: Color::TRANSPARENT,
+                    line_width: 2.0,
+                    smooth: false,
+                    min: Some(0.0),
+                    max: Some(max),
+                };
+                chart.draw(buffer, area, &state.rx_history);
+
+                chart.color.set_alpha(chart.color.alpha() * 0.5);
+                chart.draw(buffer, area, &state.tx_history);
+            }
+        }
+    }
+}
+
+/// Bytes per second received and transmitted between two samples of the counters, taken at
+/// the given instants.
+fn rates(
+    (then, rx_then, tx_then): (Instant, u64, u64),
+    (now, rx, tx): (Instant, u64, u64),
+) -> Option<(f64, f64)> {
+    let elapsed = now.checked_duration_since(then)?.as_secs_f64();
+    if elapsed == 0.0 {
+        return None;
+    }
+
+    // Counters go backwards when they wrap or the interface is reset, which isn't traffic.
+    Some((
+        rx.saturating_sub(rx_then) as f64 / elapsed,
+        tx.saturating_sub(tx_then) as f64 / elapsed,
+    ))
+}
+
+/// Reads the received and transmitted byte counters of the interface from `net/dev`.
+fn read_counters(proc_root: &Path, interface: &str) -> Option<(u64, u64)> {
+    let dev = std::fs::read_to_string(proc_root.join("net/dev")).ok()?;
+
+    // Each line is `name: rx_bytes rx_packets ... (8 receive fields) tx_bytes ...`, after two
+    // header lines.
+    let counters = dev.lines().skip(2).find_map(|line| {
+        let (name, counters) = line.split_once(':')?;
+        (name.trim() == interface).then_some(counters)
+    })?;
+
+    let counters: Vec<u64> = counters
+        .split_whitespace()
+        .filter_map(|counter| counter.parse().ok())
+        .collect();
+
+    Some((*counters.first()?, *counters.get(8)?))
+}
+
+fn read_operstate(sys_root: &Path, interface: &str) -> String {
+    std::fs::read_to_string(sys_root.join("net").join(interface).join("operstate"))
+        .map(|state| state.trim().to_string())
+        .unwrap_or_else(|_| "missing".to_string())
+}
+
+/// Lists the IPv4 and global IPv6 addresses of the interface, IPv4 first.
+fn interface_addresses(interface: &str) -> Vec<IpAddr> {
+    let mut addresses = Vec::new();
+    let mut interfaces: *mut libc::ifaddrs = std::ptr::null_mut();
+
+    // SAFETY: `interfaces` is a valid pointer for getifaddrs to store the list in.
+    if unsafe { libc::getifaddrs(&mut interfaces) } != 0 {
+        return addresses;
+    }
+
+    let mut current = interfaces;
+    while !current.is_null() {
+        // SAFETY: `current` is a non-null node of the list returned by getifaddrs, which stays
+        // alive until freeifaddrs is called below.
+        let entry = unsafe { &*current };
+        current = entry.ifa_next;
+
+        if entry.ifa_addr.is_null() || entry.ifa_name.is_null() {
+            continue;
+        }
+
+        // SAFETY: `ifa_name` is a non-null, nul-terminated string owned by the list.
+        let name = unsafe { CStr::from_ptr(entry.ifa_name) };
+        if name.to_bytes() != interface.as_bytes() {
+            continue;
+        }
+
+        // SAFETY: `ifa_addr` is non-null and its family tells which sockaddr it points to.
+        let address = unsafe {
+            match (*entry.ifa_addr).sa_family as i32 {
+                libc::AF_INET => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in);
+                    Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(
+                        address.sin_addr.s_addr,
+                    ))))
+                }
+                libc::AF_INET6 => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in6);
+                    Some(IpAddr::V6(Ipv6Addr::from(address.sin6_addr.s6_addr)))
+                }
+                _ => None,
+            }
+        };
+
+        match address {
+            // Link-local IPv6 addresses are on every interface and only add noise.
+            Some(IpAddr::V6(address)) if address.segments()[0] & 0xffc0 == 0xfe80 => {}
+            Some(address) => addresses.push(address),
+            None => {}
+        }
+    }
+
+    // SAFETY: `interfaces` was returned by a successful getifaddrs call and isn't used after.
+    unsafe { libc::freeifaddrs(interfaces) };
+
+    addresses.sort_by_key(|address| address.is_ipv6());
+    addresses
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    /// A root holding `contents` at `path`, for either procfs or sysfs.
+    fn root(test: &str, path: &str, contents: &str) -> TempDir {
+        let root = TempDir::new(&format!("network-{test}"));
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+
+        root
+    }
+
+    const DEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    1000      10    0    0    0     0          0         0     1000      10    0    0    0     0       0          0
+  eth0:12345678901 9000    0    0    0     0          0         0 987654321    7000    0    0    0     0       0          0
+";
+
+    #[test]
+    fn counters_are_read_even_without_a_space_after_the_colon() {
+        let proc = root("counters", "net/dev", DEV);
+
+        assert_eq!(read_counters(&proc, "lo"), Some((1000, 1000)));
+        assert_eq!(read_counters(&proc, "eth0"), Some((12345678901, 987654321)));
+        assert_eq!(read_counters(&proc, "wlan0"), None);
+        assert_eq!(read_counters(&proc.join("missing"), "eth0"), None);
+    }
+
+    #[test]
+    fn rates_ignore_counters_going_backwards() {
+        let then = Instant::now();
+        let now = then + Duration::from_secs(2);
+
+        assert_eq!(
+            rates((then, 1000, 500), (now, 3000, 1500)),
+            Some((1000.0, 500.0))
+        );
+        // A wrapped or reset counter shows no traffic rather than a huge rate.
+        assert_eq!(
+            rates((then, u32::MAX as u64, 1000), (now, 100, 3000)),
+            Some((0.0, 1000.0))
+        );
+        assert_eq!(rates((then, 0, 0), (then, 100, 100)), None);
+        assert_eq!(rates((now, 0, 0), (then, 100, 100)), None);
+    }
+
+    #[test]
+    fn operstate_is_read_or_reported_missing() {
+        let sys = root("operstate", "net/eth0/operstate", "down\n");
+
+        assert_eq!(read_operstate(&sys, "eth0"), "down");
+        assert_eq!(read_operstate(&sys, "wlan0"), "missing");
+    }
+}
@@ -0,0 +1,886 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use spin::RwLock;
+use tiny_skia::Pixmap;
+use tiny_skia::PixmapPaint;
+use tiny_skia::Rect;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event::ElementState;
+use winit::event::MouseButton;
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoop;
+use winit::event_loop::EventLoopProxy;
+use winit::keyboard::Key;
+use winit::keyboard::NamedKey;
+use zbus::blocking::Connection;
+use zbus::blocking::MessageIterator;
+use zbus::message::Type as MessageType;
+use zbus::zvariant::OwnedValue;
+use zbus::MatchRule;
+
+use crate::config::MeterConfig;
+use crate::config::NowPlayingConfig;
+use crate::config::TextConfig;
+
+use super::image_pixmap;
+use super::system::draw_bar;
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+use super::BAR_SPACING;
+
+/// Prefix of the bus names MPRIS players own, followed by the player name.
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// How often players are polled, changes announced on the bus are picked up right away.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Space between the album art and the text.
+const ART_SPACING: f32 = 16.0;
+
+/// Largest album art download accepted, in bytes.
+const MAX_ART_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl Status {
+    fn parse(status: &str) -> Self {
+        match status {
+            "Playing" => Status::Playing,
+            "Paused" => Status::Paused,
+            _ => Status::Stopped,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Playing => "Playing",
+            Status::Paused => "Paused",
+            Status::Stopped => "Stopped",
+        }
+    }
+}
+
+/// What a player reports about the current track.
+#[derive(Debug, Clone, PartialEq)]
+struct Track {
+    title: String,
+    artist: String,
+    album: String,
+    art_url: Option<String>,
+    length: Option<Duration>,
+    position: Duration,
+    status: Status,
+}
+
+/// The current track along with what is needed to draw it.
+struct Playback {
+    track: Track,
+    art: Option<Arc<Pixmap>>,
+    /// When the position was read, used to move the progress along between polls.
+    read_at: Instant,
+}
+
+/// Player controls, named after the MPRIS methods they call.
+#[derive(Debug, Clone, Copy)]
+enum Action {
+    Play,
+    Pause,
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+}
+
+impl Action {
+    fn method(&self) -> &'static str {
+        match self {
+            Action::Play => "Play",
+            Action::Pause => "Pause",
+            Action::PlayPause => "PlayPause",
+            Action::Next => "Next",
+            Action::Previous => "Previous",
+            Action::Stop => "Stop",
+        }
+    }
+}
+
+enum Command {
+    Refresh,
+    Send(Action),
+}
+
+pub struct NowPlaying {
+    text_widget: Text,
+    position: Position,
+    format: String,
+    art_size: u32,
+    progress: Option<MeterConfig>,
+    playback: Arc<RwLock<Option<Playback>>>,
+    commands: Sender<Command>,
+    measured: Measured,
+}
+
+/// Sizes worked out while measuring, which the parts are drawn at.
+#[derive(Default, Clone, Copy)]
+struct Measured {
+    height: f32,
+    text_height: f32,
+    /// Height of the text and the progress bar below it.
+    column_height: f32,
+    /// How far the track got, between 0 and 1.
+    fraction: f32,
+}
+
+impl NowPlaying {
+    pub fn new(event_loop: &EventLoop<()>, config: NowPlayingConfig) -> Result<Self, WidgetError> {
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let playback = Arc::new(RwLock::new(None));
+        let (commands, receiver) = mpsc::channel();
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let playback = playback.clone();
+            let commands = commands.clone();
+            let player = config.player;
+            let bus_address = config.bus_address;
+            let art_size = config.art_size;
+
+            std::thread::spawn(move || {
+                let connection = match bus_address {
+                    Some(address) => zbus::blocking::connection::Builder::address(address.as_str())
+                        .and_then(|builder| builder.build()),
+                    None => Connection::session(),
+                };
+
+                let connection = match connection {
+                    Ok(connection) => connection,
+                    Err(err) => {
+                        println!("Error connecting to the bus: {err}");
+                        return;
+                    }
+                };
+
+                listen(&connection, commands);
+
+                let mut worker = Worker {
+                    connection,
+                    player,
+                    art_size,
+                    art: None,
+                    playback,
+                    event_loop_proxy,
+                };
+                worker.run(receiver);
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            position: config.position,
+            format: config.format,
+            art_size: config.art_size,
+            progress: config.progress,
+            playback,
+            commands,
+            measured: Measured::default(),
+        })
+    }
+
+    fn send(&self, action: Action) {
+        let _ = self.commands.send(Command::Send(action));
+    }
+}
+
+impl Widget for NowPlaying {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let playback = self.playback.read();
+        let playback = playback.as_ref()?;
+
+        let track = &playback.track;
+        let position = match track.status {
+            Status::Playing => track.position + playback.read_at.elapsed(),
+            _ => track.position,
+        };
+        let position = match track.length {
+            Some(length) => position.min(length),
+            None => position,
+        };
+
+        let text = self
+            .format
+            .replace("{title}", &track.title)
+            .replace("{artist}", &track.artist)
+            .replace("{album}", &track.album)
+            .replace("{status}", track.status.label())
+            .replace("{position}", &format_duration(position))
+            .replace(
+                "{length}",
+                &track.length.map(format_duration).unwrap_or_default(),
+            );
+        self.text_widget.update_data(text);
+
+        let (text_width, text_height) = self.text_widget.layout(space);
+
+        let art_size = self.art_size as f32;
+        let art_width = match self.art_size {
+            0 => 0.0,
+            _ => art_size + ART_SPACING,
+        };
+
+        let (column_width, column_height) = match &self.progress {
+            Some(progress) => (
+                text_width.max(progress.width),
+                text_height + BAR_SPACING + progress.height,
+            ),
+            None => (text_width, text_height),
+        };
+
+        let height = art_size.max(column_height);
+        self.measured = Measured {
+            height,
+            text_height,
+            column_height,
+            fraction: match track.length {
+                Some(length) if !length.is_zero() => position.as_secs_f32() / length.as_secs_f32(),
+                _ => 0.0,
+            },
+        };
+
+        Some((art_width + column_width, height))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let Measured {
+            height,
+            text_height,
+            column_height,
+            fraction,
+        } = self.measured;
+
+        let art_size = self.art_size as f32;
+        let art_width = match self.art_size {
+            0 => 0.0,
+            _ => art_size + ART_SPACING,
+        };
+
+        let art = self
+            .playback
+            .read()
+            .as_ref()
+            .and_then(|playback| playback.art.clone());
+        if let Some(art) = art {
+            buffer.draw_pixmap(
+                x,
+                y + ((height - art_size) / 2.0) as i32,
+                art.as_ref().as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+
+        let column_x = x as f32 + art_width;
+        let column_y = y as f32 + (height - column_height) / 2.0;
+        self.text_widget
+            .paint(buffer, column_x as i32, column_y as i32);
+
+        if let Some(progress) = &self.progress {
+            let area = Rect::from_xywh(
+                column_x,
+                column_y + text_height + BAR_SPACING,
+                progress.width,
+                progress.height,
+            );
+            if let Some(area) = area {
+                draw_bar(buffer, area, progress, fraction);
+            }
+        }
+    }
+
+    fn on_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                let action = match event.logical_key {
+                    Key::Named(NamedKey::MediaPlay) => Action::Play,
+                    Key::Named(NamedKey::MediaPause) => Action::Pause,
+                    Key::Named(NamedKey::MediaPlayPause) => Action::PlayPause,
+                    Key::Named(NamedKey::MediaTrackNext) => Action::Next,
+                    Key::Named(NamedKey::MediaTrackPrevious) => Action::Previous,
+                    Key::Named(NamedKey::MediaStop) => Action::Stop,
+                    _ => return false,
+                };
+
+                self.send(action);
+                true
+            }
+
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => {
+                self.send(Action::PlayPause);
+                true
+            }
+
+            _ => false,
+        }
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+}
+
+/// Asks the worker to refresh every time a player announces that its properties changed.
+fn listen(connection: &Connection, commands: Sender<Command>) {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface("org.freedesktop.DBus.Properties")
+        .and_then(|rule| rule.member("PropertiesChanged"))
+        .and_then(|rule| rule.path(MPRIS_PATH))
+        .map(|rule| rule.build());
+
+    let messages = rule.and_then(|rule| MessageIterator::for_match_rule(rule, connection, None));
+    let messages = match messages {
+        Ok(messages) => messages,
+        Err(err) => {
+            println!("Error subscribing to player changes: {err}");
+            return;
+        }
+    };
+
+    std::thread::spawn(move || {
+        for message in messages {
+            if message.is_err() || commands.send(Command::Refresh).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+struct Worker {
+    connection: Connection,
+    player: Option<String>,
+    art_size: u32,
+    /// Album art of the current track along with the URL it was loaded from.
+    art: Option<(String, Option<Arc<Pixmap>>)>,
+    playback: Arc<RwLock<Option<Playback>>>,
+    event_loop_proxy: EventLoopProxy<()>,
+}
+
+impl Worker {
+    fn run(&mut self, commands: Receiver<Command>) {
+        loop {
+            let command = match commands.recv_timeout(POLL_INTERVAL) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            let name = match find_player(&self.connection, self.player.as_deref()) {
+                Ok(name) => name,
+                Err(err) => {
+                    println!("Error listing players: {err}");
+                    None
+                }
+            };
+
+            if let (Some(Command::Send(action)), Some(name)) = (&command, &name) {
+                if let Err(err) = call(&self.connection, name, action.method()) {
+                    println!("Error controlling player: {err}");
+                }
+            }
+
+            let track = name.and_then(|name| read_track(&self.connection, &name).ok());
+            self.update(track);
+        }
+    }
+
+    fn update(&mut self, track: Option<Track>) {
+        let Some(track) = track else {
+            if self.playback.write().take().is_some() {
+                self.event_loop_proxy.send_event(()).unwrap();
+            }
+            return;
+        };
+
+        let art = match &track.art_url {
+            Some(url) if self.art_size > 0 => match &self.art {
+                Some((loaded, art)) if loaded == url => art.clone(),
+                _ => {
+                    let art = load_art(url, self.art_size).map(Arc::new);
+                    self.art = Some((url.clone(), art.clone()));
+                    art
+                }
+            },
+            _ => None,
+        };
+
+        // Redraw while playing so the progress keeps moving, otherwise only when something changed.
+        let changed = self
+            .playback
+            .read()
+            .as_ref()
+            .is_none_or(|playback| playback.track != track);
+
+        if changed || track.status == Status::Playing {
+            *self.playback.write() = Some(Playback {
+                track,
+                art,
+                read_at: Instant::now(),
+            });
+            self.event_loop_proxy.send_event(()).unwrap();
+        }
+    }
+}
+
+/// Finds the configured player, or the first one that is playing when none was configured.
+fn find_player(connection: &Connection, wanted: Option<&str>) -> zbus::Result<Option<String>> {
+    let reply = connection.call_method(
+        Some("org.freedesktop.DBus"),
+        "/org/freedesktop/DBus",
+        Some("org.freedesktop.DBus"),
+        "ListNames",
+        &(),
+    )?;
+    let names: Vec<String> = reply.body().deserialize()?;
+
+    let mut players = names
+        .into_iter()
+        .filter(|name| name.starts_with(MPRIS_PREFIX));
+
+    if let Some(wanted) = wanted {
+        // Players running more than once add an instance suffix, e.g. `vlc.instance1234`.
+        return Ok(players.find(|name| {
+            let player = &name[MPRIS_PREFIX.len()..];
+            player == wanted
+                || player
+                    .strip_prefix(wanted)
+                    .is_some_and(|instance| instance.starts_with('.'))
+        }));
+    }
+
+    let players: Vec<String> = players.collect();
+    let playing = players.iter().find(|name| {
+        properties(connection, name)
+            .ok()
+            .and_then(|properties| string(&properties, "PlaybackStatus"))
+            .is_some_and(|status| Status::parse(&status) == Status::Playing)
+    });
+
+    Ok(playing.or(players.first()).cloned())
+}
+
+fn properties(connection: &Connection, name: &str) -> zbus::Result<HashMap<String, OwnedValue>> {
+    let reply = connection.call_method(
+        Some(name),
+        MPRIS_PATH,
+        Some("org.freedesktop.DBus.Properties"),
+        "GetAll",
+        &(PLAYER_INTERFACE,),
+    )?;
+
+    reply.body().deserialize()
+}
+
+fn read_track(connection: &Connection, name: &str) -> zbus::Result<Track> {
+    let properties = properties(connection, name)?;
+
+    let metadata: HashMap<String, OwnedValue> = properties
+        .get("Metadata")
+        .and_then(|metadata| metadata.try_clone().ok())
+        .and_then(|metadata| metadata.try_into().ok())
+        .unwrap_or_default();
+
+    let artist = metadata
+        .get("xesam:artist")
+        .and_then(|artist| artist.try_clone().ok())
+        .and_then(|artist| Vec::<String>::try_from(artist).ok())
+        .map(|artists| artists.join(", "))
+        .unwrap_or_default();
+
+    Ok(Track {
+        title: string(&metadata, "xesam:title").unwrap_or_default(),
+        artist,
+        album: string(&metadata, "xesam:album").unwrap_or_default(),
+        art_url: string(&metadata, "mpris:artUrl").filter(|url| !url.is_empty()),
+        length: microseconds(&metadata, "mpris:length"),
+        position: microseconds(&properties, "Position").unwrap_or_default(),
+        status: string(&properties, "PlaybackStatus")
+            .map(|status| Status::parse(&status))
+            .unwrap_or(Status::Stopped),
+    })
+}
+
+fn call(connection: &Connection, name: &str, method: &str) -> zbus::Result<()> {
+    connection.call_method(Some(name), MPRIS_PATH, Some(PLAYER_INTERFACE), method, &())?;
+
+    Ok(())
+}
+
+fn string(map: &HashMap<String, OwnedValue>, key: &str) -> Option<String> {
+    let value = map.get(key)?.try_clone().ok()?;
+    String::try_from(value).ok()
+}
+
+/// Reads a duration in microseconds, players disagree on whether it is signed or not.
+fn microseconds(map: &HashMap<String, OwnedValue>, key: &str) -> Option<Duration> {
+    let value = map.get(key)?;
+
+    let micros = match value.try_clone().ok().map(i64::try_from) {
+        Some(Ok(micros)) => u64::try_from(micros).ok()?,
+        _ => u64::try_from(value.try_clone().ok()?).ok()?,
+    };
+
+    Some(Duration::from_micros(micros))
+}
+
+/// Loads album art from a `file://` or `http(s)://` URL, which is what players hand out.
+fn load_art(url: &str, size: u32) -> Option<Pixmap> {
+    let bytes = if let Some(path) = url.strip_prefix("file://") {
+        std::fs::read(percent_decode(path)).ok()?
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        let response = ureq::get(url)
+            .timeout(Duration::from_secs(30))
+            .call()
+            .ok()?;
+
+        let mut bytes = Vec::new();
+        response
+            .into_reader()
+            .take(MAX_ART_SIZE)
+            .read_to_end(&mut bytes)
+            .ok()?;
+        bytes
+    } else {
+        return None;
+    };
+
+    let image = image::load_from_memory(&bytes).ok()?;
+
+    image_pixmap(&image, size, size)
+}
+
+/// Decodes the `%XX` escapes of a URL path.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    match seconds / 3600 {
+        0 => format!("{}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{hours}:{:02}:{:02}", seconds / 60 % 60, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::path::PathBuf;
+    use std::process::Child;
+    use std::process::Command as Process;
+    use std::process::Stdio;
+    use std::sync::Mutex;
+
+    use zbus::zvariant::Value;
+
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    /// A private bus, stopped when dropped.
+    struct Bus {
+        daemon: Child,
+        address: String,
+        dir: PathBuf,
+    }
+
+    impl Bus {
+        /// Starts a bus, or returns `None` when `dbus-daemon` isn't installed.
+        fn start(test: &str) -> Option<Self> {
+            let dir =
+                std::env::temp_dir().join(format!("russ-mpris-{test}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+
+            let daemon = Process::new("dbus-daemon")
+                .arg("--session")
+                .arg("--nofork")
+                .arg(format!("--address=unix:path={}", dir.join("bus").display()))
+                .arg("--print-address=1")
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn();
+            let Ok(mut daemon) = daemon else {
+                eprintln!("dbus-daemon is not installed, skipping");
+                return None;
+            };
+
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+                dir,
+            })
+        }
+
+        fn connect(&self) -> Connection {
+            zbus::blocking::connection::Builder::address(self.address.as_str())
+                .unwrap()
+                .build()
+                .unwrap()
+        }
+
+        /// Puts a player on the bus under `org.mpris.MediaPlayer2.<name>`.
+        fn player(&self, name: &str, player: MockPlayer) -> Connection {
+            zbus::blocking::connection::Builder::address(self.address.as_str())
+                .unwrap()
+                .name(format!("{MPRIS_PREFIX}{name}"))
+                .unwrap()
+                .serve_at(MPRIS_PATH, player)
+                .unwrap()
+                .build()
+                .unwrap()
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    struct MockPlayer {
+        status: &'static str,
+        title: &'static str,
+        calls: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl MockPlayer {
+        fn new(status: &'static str, title: &'static str) -> Self {
+            Self {
+                status,
+                title,
+                calls: Arc::default(),
+            }
+        }
+    }
+
+    #[zbus::interface(name = "org.mpris.MediaPlayer2.Player")]
+    impl MockPlayer {
+        fn play_pause(&mut self) {
+            self.calls.lock().unwrap().push("PlayPause");
+        }
+
+        fn next(&mut self) {
+            self.calls.lock().unwrap().push("Next");
+        }
+
+        #[zbus(property)]
+        fn playback_status(&self) -> String {
+            self.status.to_string()
+        }
+
+        #[zbus(property)]
+        fn position(&self) -> i64 {
+            83_000_000
+        }
+
+        #[zbus(property)]
+        fn metadata(&self) -> HashMap<String, OwnedValue> {
+            let value = |value: Value| value.try_to_owned().unwrap();
+
+            HashMap::from([
+                ("xesam:title".to_string(), value(self.title.into())),
+                ("xesam:artist".to_string(), value(vec!["Ann", "Bob"].into())),
+                ("xesam:album".to_string(), value("Album".into())),
+                ("mpris:length".to_string(), value(240_000_000_i64.into())),
+                ("mpris:artUrl".to_string(), value("".into())),
+            ])
+        }
+    }
+
+    #[test]
+    fn track_is_read_from_the_player() {
+        let Some(bus) = Bus::start("track") else {
+            return;
+        };
+        let _player = bus.player("mock", MockPlayer::new("Paused", "Song"));
+        let connection = bus.connect();
+
+        let track = read_track(&connection, &format!("{MPRIS_PREFIX}mock")).unwrap();
+
+        assert_eq!(
+            track,
+            Track {
+                title: "Song".to_string(),
+                artist: "Ann, Bob".to_string(),
+                album: "Album".to_string(),
+                art_url: None,
+                length: Some(Duration::from_secs(240)),
+                position: Duration::from_secs(83),
+                status: Status::Paused,
+            }
+        );
+    }
+
+    #[test]
+    fn playing_player_is_preferred_unless_one_is_configured() {
+        let Some(bus) = Bus::start("select") else {
+            return;
+        };
+        let _paused = bus.player("paused", MockPlayer::new("Paused", "First"));
+        let _playing = bus.player("playing.instance42", MockPlayer::new("Playing", "Second"));
+        let connection = bus.connect();
+
+        let player = |wanted| find_player(&connection, wanted).unwrap();
+
+        assert_eq!(
+            player(None).as_deref(),
+            Some("org.mpris.MediaPlayer2.playing.instance42")
+        );
+        assert_eq!(
+            player(Some("paused")).as_deref(),
+            Some("org.mpris.MediaPlayer2.paused")
+        );
+        // Instances are matched by the player name only.
+        assert_eq!(
+            player(Some("playing")).as_deref(),
+            Some("org.mpris.MediaPlayer2.playing.instance42")
+        );
+        assert_eq!(player(Some("play")), None);
+    }
+
+    #[test]
+    fn actions_call_the_player() {
+        let Some(bus) = Bus::start("actions") else {
+            return;
+        };
+        let player = MockPlayer::new("Playing", "Song");
+        let calls = player.calls.clone();
+        let _player = bus.player("mock", player);
+        let connection = bus.connect();
+        let name = format!("{MPRIS_PREFIX}mock");
+
+        call(&connection, &name, Action::Next.method()).unwrap();
+        call(&connection, &name, Action::PlayPause.method()).unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), ["Next", "PlayPause"]);
+        assert!(call(&connection, &name, Action::Stop.method()).is_err());
+    }
+
+    #[test]
+    fn durations_may_be_signed_or_not() {
+        let map = HashMap::from([
+            ("signed".to_string(), OwnedValue::from(1_500_000_i64)),
+            ("unsigned".to_string(), OwnedValue::from(2_000_000_u64)),
+            ("negative".to_string(), OwnedValue::from(-1_i64)),
+            ("text".to_string(), Value::from("1").try_to_owned().unwrap()),
+        ]);
+
+        assert_eq!(
+            microseconds(&map, "signed"),
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(microseconds(&map, "unsigned"), Some(Duration::from_secs(2)));
+        assert_eq!(microseconds(&map, "negative"), None);
+        assert_eq!(microseconds(&map, "text"), None);
+        assert_eq!(microseconds(&map, "missing"), None);
+        assert_eq!(string(&map, "text").as_deref(), Some("1"));
+        assert_eq!(string(&map, "signed"), None);
+    }
+
+    #[test]
+    fn art_urls_are_percent_decoded() {
+        assert_eq!(
+            percent_decode("/music/My%20Album/cover.jpg"),
+            "/music/My Album/cover.jpg"
+        );
+        assert_eq!(percent_decode("/caf%C3%A9.png"), "/café.png");
+        // Broken escapes are kept as they are.
+        assert_eq!(percent_decode("/100%/a%2"), "/100%/a%2");
+    }
+
+    #[test]
+    fn art_is_loaded_from_file_urls() {
+        // The space checks that file URLs are decoded.
+        let dir = TempDir::new("now-playing art");
+        let path = dir.join("cover.png");
+        image::RgbaImage::from_pixel(4, 2, image::Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+
+        let url = format!("file://{}", path.display()).replace(' ', "%20");
+        let art = load_art(&url, 8).unwrap();
+
+        assert!(art.width() <= 8 && art.height() <= 8);
+        assert!(load_art("ftp://example.com/cover.png", 8).is_none());
+        assert!(load_art("file:///nonexistent/cover.png", 8).is_none());
+    }
+
+    #[test]
+    fn durations_are_formatted_like_players_do() {
+        assert_eq!(format_duration(Duration::from_secs(83)), "1:23");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+        assert_eq!(Status::parse("Playing"), Status::Playing);
+        assert_eq!(Status::parse("Unknown"), Status::Stopped);
+    }
+}
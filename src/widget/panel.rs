@@ -0,0 +1,374 @@
+use std::time::Instant;
+
+use tiny_skia::FillRule;
+use tiny_skia::FilterQuality;
+use tiny_skia::IntRect;
+use tiny_skia::Paint;
+use tiny_skia::Path;
+use tiny_skia::Pattern;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use tiny_skia::SpreadMode;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::EventLoop;
+
+use crate::config::Backdrop;
+use crate::config::Fill;
+use crate::config::PanelConfig;
+use crate::config::StrokeConfig;
+use crate::effects;
+
+use super::from_config;
+use super::rounded_rect;
+use super::shape::draw_shape;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// A shape drawn behind another widget, e.g. a translucent box that makes text easier to read.
+pub struct Panel {
+    width: f32,
+    height: f32,
+    padding: f32,
+    corner_radius: f32,
+    fill: Option<Fill>,
+    stroke: Option<StrokeConfig>,
+    backdrop: Option<Backdrop>,
+    /// The blurred backdrop from an earlier frame, reused until the background changes or the
+    /// panel moves. Widgets drawn under the panel don't update it.
+    backdrop_cache: Option<BackdropCache>,
+    position: Position,
+    child: Option<Box<dyn Widget>>,
+    /// Size of the panel as last measured, around the child when it has one.
+    size: (f32, f32),
+}
+
+impl Panel {
+    pub fn new(event_loop: &EventLoop<()>, config: PanelConfig) -> Result<Self, WidgetError> {
+        let child = config
+            .child
+            .map(|child| from_config(event_loop, *child))
+            .transpose()?;
+
+        Ok(Self {
+            width: config.width,
+            height: config.height,
+            padding: config.padding,
+            corner_radius: config.corner_radius,
+            fill: config.fill,
+            stroke: config.stroke,
+            backdrop: config.backdrop,
+            backdrop_cache: None,
+            position: config.position,
+            child,
+            size: (0.0, 0.0),
+        })
+    }
+
+    fn draw_panel(&mut self, buffer: &mut Pixmap, area: Rect) {
+        let Some(path) = rounded_rect(area, self.corner_radius) else {
+            return;
+        };
+
+        if let Some(backdrop) = self.backdrop {
+            self.draw_backdrop(buffer, &path, area, backdrop);
+        }
+
+        draw_shape(
+            buffer,
+            &path,
+            area,
+            self.fill.as_ref(),
+            self.stroke.as_ref(),
+        );
+    }
+
+    /// Blurs what was drawn under the panel so far, which is usually the background.
+    fn draw_backdrop(&mut self, buffer: &mut Pixmap, path: &Path, area: Rect, backdrop: Backdrop) {
+        // Pixels around the panel are blurred too so its edges blend with them instead of
+        // smearing the last row of pixels inside.
+        let margin = (backdrop.blur * 3.0).ceil();
+        let source = Rect::from_ltrb(
+            area.left() - margin,
+            area.top() - margin,
+            area.right() + margin,
+            area.bottom() + margin,
+        )
+        .and_then(|source| source.round_out())
+        .and_then(|source| {
+            source.intersect(&IntRect::from_xywh(0, 0, buffer.width(), buffer.height())?)
+        });
+        let Some(source) = source else {
+            return;
+        };
+        let cached = self
+            .backdrop_cache
+            .as_ref()
+            .is_some_and(|cache| cache.backdrop == backdrop && cache.area == source);
+
+        if !cached {
+            let Some(mut blurred) = buffer.clone_rect(source) else {
+                return;
+            };
+            effects::blur(&mut blurred, backdrop.blur);
+            effects::brightness(&mut blurred, backdrop.brightness);
+            if let Some(tint) = backdrop.tint {
+                effects::tint(&mut blurred, tint);
+            }
+
+            self.backdrop_cache = Some(BackdropCache {
+                backdrop,
+                area: source,
+                blurred,
+            });
+        }
+
+        let Some(cache) = &self.backdrop_cache else {
+            return;
+        };
+
+        let paint = Paint {
+            shader: Pattern::new(
+                cache.blurred.as_ref(),
+                SpreadMode::Pad,
+                FilterQuality::Nearest,
+                1.0,
+                Transform::from_translate(source.x() as f32, source.y() as f32),
+            ),
+            anti_alias: true,
+            ..Default::default()
+        };
+        buffer.fill_path(path, &paint, FillRule::Winding, Transform::identity(), None);
+    }
+}
+
+struct BackdropCache {
+    backdrop: Backdrop,
+    /// Part of the window that was blurred.
+    area: IntRect,
+    blurred: Pixmap,
+}
+
+impl Widget for Panel {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let padding = self.padding * 2.0;
+
+        self.size = match self.child.as_mut() {
+            Some(child) => {
+                let space = ((space.0 - padding).max(0.0), (space.1 - padding).max(0.0));
+                let (width, height) = child.measure(window, space)?;
+
+                (width + padding, height + padding)
+            }
+            None => (self.width, self.height),
+        };
+
+        Some(self.size)
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        if let Some(area) = Rect::from_xywh(x as f32, y as f32, self.size.0, self.size.1) {
+            self.draw_panel(buffer, area);
+        }
+
+        if let Some(child) = self.child.as_mut() {
+            let padding = self.padding.round() as i32;
+            child.draw_at(window, buffer, (x + padding, y + padding));
+        }
+    }
+
+    fn on_event(&mut self, event: &WindowEvent) -> bool {
+        self.child
+            .as_mut()
+            .is_some_and(|child| child.on_event(event))
+    }
+
+    fn on_resize(&mut self, size: PhysicalSize<u32>) {
+        self.backdrop_cache = None;
+
+        if let Some(child) = self.child.as_mut() {
+            child.on_resize(size);
+        }
+    }
+
+    fn on_background_change(&mut self) {
+        self.backdrop_cache = None;
+
+        if let Some(child) = self.child.as_mut() {
+            child.on_background_change();
+        }
+    }
+
+    fn on_tick(&mut self, now: Instant) {
+        if let Some(child) = self.child.as_mut() {
+            child.on_tick(now);
+        }
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.child.as_ref().and_then(|child| child.next_tick())
+    }
+
+    fn focusable(&self) -> bool {
+        self.child.as_ref().is_some_and(|child| child.focusable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use tiny_skia::Color;
+
+    use crate::config::RectangleConfig;
+    use crate::widget::shape::Rectangle;
+
+    use super::*;
+
+    const WINDOW: PhysicalSize<u32> = PhysicalSize::new(40, 40);
+
+    fn panel(padding: f32, child: Option<Box<dyn Widget>>) -> Panel {
+        Panel {
+            width: 30.0,
+            height: 8.0,
+            padding,
+            corner_radius: 0.0,
+            fill: Some(Fill::Color((0, 0, 255, 255))),
+            stroke: None,
+            backdrop: None,
+            backdrop_cache: None,
+            position: Position::default(),
+            child,
+            size: (0.0, 0.0),
+        }
+    }
+
+    fn rectangle(width: f32, height: f32) -> Box<dyn Widget> {
+        Box::new(Rectangle::new(RectangleConfig {
+            width,
+            height,
+            corner_radius: 0.0,
+            fill: Some(Fill::Color((255, 0, 0, 255))),
+            stroke: None,
+            position: Position::XY { x: 3, y: 4 },
+        }))
+    }
+
+    fn pixel(buffer: &Pixmap, x: u32, y: u32) -> (u8, u8, u8, u8) {
+        let pixel = buffer.pixel(x, y).unwrap();
+        (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha())
+    }
+
+    #[test]
+    fn panel_without_child_keeps_its_size() {
+        let mut panel = panel(5.0, None);
+
+        assert_eq!(panel.measure(WINDOW, (40.0, 40.0)), Some((30.0, 8.0)));
+        assert!(matches!(panel.position(), Position::Center));
+    }
+
+    #[test]
+    fn panel_wraps_its_child_and_draws_it_in_place() {
+        let mut panel = panel(5.0, Some(rectangle(10.0, 10.0)));
+        let mut buffer = Pixmap::new(WINDOW.width, WINDOW.height).unwrap();
+
+        assert_eq!(panel.measure(WINDOW, (40.0, 40.0)), Some((20.0, 20.0)));
+        // The panel is placed by its own position, not the child's.
+        assert!(matches!(panel.position(), Position::Center));
+
+        panel.draw_at(WINDOW, &mut buffer, (2, 3));
+
+        assert_eq!(pixel(&buffer, 3, 4), (0, 0, 255, 255));
+        assert_eq!(pixel(&buffer, 12, 13), (255, 0, 0, 255));
+        assert_eq!(pixel(&buffer, 21, 22), (0, 0, 255, 255));
+        // Nothing outside of the panel is touched.
+        assert_eq!(pixel(&buffer, 22, 23), (0, 0, 0, 0));
+        assert_eq!(pixel(&buffer, 1, 2), (0, 0, 0, 0));
+    }
+
+    /// Child recording the space it is measured in.
+    struct Probe(Rc<Cell<(f32, f32)>>);
+
+    impl Widget for Probe {
+        fn position(&self) -> &Position {
+            &Position::Center
+        }
+
+        fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+            self.0.set(space);
+            Some((1.0, 1.0))
+        }
+
+        fn draw_at(
+            &mut self,
+            _window: PhysicalSize<u32>,
+            _buffer: &mut Pixmap,
+            _origin: (i32, i32),
+        ) {
+        }
+    }
+
+    #[test]
+    fn child_gets_the_space_left_inside_the_padding() {
+        let space = Rc::new(Cell::new((0.0, 0.0)));
+        let mut panel = panel(5.0, Some(Box::new(Probe(space.clone()))));
+
+        assert_eq!(panel.measure(WINDOW, (30.0, 8.0)), Some((11.0, 11.0)));
+        assert_eq!(space.get(), (20.0, 0.0));
+    }
+
+    #[test]
+    fn backdrop_is_blurred_again_only_when_the_background_changes_or_the_panel_moves() {
+        let mut panel = Panel {
+            fill: None,
+            backdrop: Some(Backdrop {
+                blur: 1.0,
+                brightness: 1.0,
+                tint: None,
+            }),
+            ..panel(0.0, None)
+        };
+        let mut buffer = Pixmap::new(WINDOW.width, WINDOW.height).unwrap();
+        panel.measure(WINDOW, (40.0, 40.0));
+
+        let mut draw = |panel: &mut Panel, color: Color, origin: (i32, i32)| {
+            buffer.fill(color);
+            panel.draw_at(WINDOW, &mut buffer, origin);
+            pixel(&buffer, origin.0 as u32 + 15, origin.1 as u32 + 4)
+        };
+
+        assert_eq!(
+            draw(&mut panel, Color::from_rgba8(255, 0, 0, 255), (0, 0)),
+            (255, 0, 0, 255)
+        );
+        // The blurred red backdrop is reused.
+        assert_eq!(
+            draw(&mut panel, Color::from_rgba8(0, 255, 0, 255), (0, 0)),
+            (255, 0, 0, 255)
+        );
+
+        panel.on_background_change();
+        assert_eq!(
+            draw(&mut panel, Color::from_rgba8(0, 255, 0, 255), (0, 0)),
+            (0, 255, 0, 255)
+        );
+
+        assert_eq!(
+            draw(&mut panel, Color::from_rgba8(0, 0, 255, 255), (2, 2)),
+            (0, 0, 255, 255)
+        );
+
+        panel.on_resize(WINDOW);
+        assert_eq!(
+            draw(&mut panel, Color::from_rgba8(255, 0, 0, 255), (2, 2)),
+            (255, 0, 0, 255)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use thiserror::Error;
+use tiny_skia::FillRule;
+use tiny_skia::FilterQuality;
+use tiny_skia::Paint;
+use tiny_skia::Pattern;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use tiny_skia::SpreadMode;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+
+use crate::animation;
+use crate::animation::Playback;
+use crate::config::ImageConfig;
+
+use super::image_pixmap;
+use super::rounded_rect;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+pub struct Picture {
+    frames: Vec<Pixmap>,
+    playback: Playback,
+    position: Position,
+    opacity: f32,
+    corner_radius: f32,
+}
+
+impl Picture {
+    pub fn new(config: ImageConfig) -> Result<Self, WidgetError> {
+        let (frames, delays) = load(&config).map_err(|err| {
+            println!("Error loading {}: {err}", config.path);
+            WidgetError
+        })?;
+
+        Ok(Self {
+            frames,
+            playback: Playback::new(delays, config.animation),
+            position: config.position,
+            opacity: config.opacity.clamp(0.0, 1.0),
+            corner_radius: config.corner_radius.max(0.0),
+        })
+    }
+}
+
+impl Widget for Picture {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, _space: (f32, f32)) -> Option<(f32, f32)> {
+        let pixmap = self.frames.get(self.playback.current())?;
+
+        Some((pixmap.width() as f32, pixmap.height() as f32))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let Some(pixmap) = self.frames.get(self.playback.current()) else {
+            return;
+        };
+
+        let (x, y) = (x as f32, y as f32);
+        let Some(area) = Rect::from_xywh(x, y, pixmap.width() as f32, pixmap.height() as f32)
+        else {
+            return;
+        };
+
+        // The image is used as the pattern a rectangle is filled with, which takes care of the
+        // rounded corners and the opacity in one go.
+        let paint = Paint {
+            shader: Pattern::new(
+                pixmap.as_ref(),
+                SpreadMode::Pad,
+                FilterQuality::Nearest,
+                self.opacity,
+                Transform::from_translate(x, y),
+            ),
+            anti_alias: true,
+            ..Default::default()
+        };
+
+        if let Some(path) = rounded_rect(area, self.corner_radius) {
+            buffer.fill_path(
+                &path,
+                &paint,
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    fn on_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::Occluded(hidden) => {
+                self.playback.set_hidden(*hidden, Instant::now());
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn on_tick(&mut self, now: Instant) {
+        self.playback.advance(now);
+    }
+
+    fn next_tick(&self) -> Option<Instant> {
+        self.playback.next_tick()
+    }
+}
+
+/// Reads the image and renders every frame of it at the size it is going to be drawn at, along
+/// with how long each frame is shown.
+fn load(config: &ImageConfig) -> Result<(Vec<Pixmap>, Vec<Duration>), LoadError> {
+    let path = Path::new(&config.path);
+
+    let is_svg = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"));
+
+    if is_svg {
+        let mut options = resvg::usvg::Options {
+            resources_dir: path.parent().map(Path::to_path_buf),
+            ..Default::default()
+        };
+        options.fontdb_mut().load_system_fonts();
+
+        let tree = resvg::usvg::Tree::from_data(&std::fs::read(path)?, &options)?;
+        let (width, height) = target_size(config, tree.size().width(), tree.size().height());
+
+        let mut pixmap = Pixmap::new(width, height).ok_or(LoadError::Size)?;
+        let transform = Transform::from_scale(
+            width as f32 / tree.size().width(),
+            height as f32 / tree.size().height(),
+        );
+        resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+        return Ok((vec![pixmap], vec![Duration::ZERO]));
+    }
+
+    let frames = animation::decode(path)?;
+    let Some(first) = frames.first() else {
+        return Err(LoadError::Size);
+    };
+    let (width, height) = target_size(
+        config,
+        first.image.width() as f32,
+        first.image.height() as f32,
+    );
+
+    let pixmaps = frames
+        .iter()
+        .map(|frame| image_pixmap(&frame.image, width, height).ok_or(LoadError::Size))
+        .collect::<Result<_, _>>()?;
+    let delays = frames.iter().map(|frame| frame.delay).collect();
+
+    Ok((pixmaps, delays))
+}
+
+/// Works out the size to draw an image of the given natural size at.
+fn target_size(config: &ImageConfig, width: f32, height: f32) -> (u32, u32) {
+    let (width, height) = match (config.width, config.height) {
+        (Some(target_width), Some(target_height)) => {
+            let factor = (target_width / width).min(target_height / height);
+            (width * factor, height * factor)
+        }
+        (Some(target_width), None) => (target_width, height * target_width / width),
+        (None, Some(target_height)) => (width * target_height / height, target_height),
+        (None, None) => (width, height),
+    };
+
+    (
+        (width * config.scale).round().max(1.0) as u32,
+        (height * config.scale).round().max(1.0) as u32,
+    )
+}
+
+#[derive(Debug, Error)]
+enum LoadError {
+    #[error("could not open image due to: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("there was an error processing the image: {0}")]
+    Image(#[from] image::ImageError),
+
+    #[error("there was an error processing the svg: {0}")]
+    Svg(#[from] resvg::usvg::Error),
+
+    #[error("the image has no area")]
+    Size,
+}
@@ -0,0 +1,489 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+use std::time::Instant;
+
+use tiny_skia::Color;
+use tiny_skia::LineCap;
+use tiny_skia::Paint;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Stroke;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event::WindowEvent;
+use winit::keyboard::Key;
+use winit::keyboard::ModifiersState;
+
+use crate::config::PomodoroConfig;
+use crate::config::PomodoroKeys;
+use crate::config::TextConfig;
+use crate::config::TimerMode;
+
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// Number of segments used to approximate a full circle of the progress ring.
+const RING_SEGMENTS: usize = 180;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl Phase {
+    fn label(&self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::ShortBreak => "Short break",
+            Phase::LongBreak => "Long break",
+        }
+    }
+}
+
+pub struct Pomodoro {
+    text_widget: Text,
+    position: Position,
+    mode: TimerMode,
+    keys: PomodoroKeys,
+    /// Modifiers held down, keys pressed along with any but shift are left to other bindings.
+    modifiers: ModifiersState,
+
+    ring_radius: f32,
+    ring_width: f32,
+    work_color: Color,
+    break_color: Color,
+
+    work_duration: Duration,
+    short_break_duration: Duration,
+    long_break_duration: Duration,
+    cycles_before_long_break: u32,
+
+    phase: Phase,
+    /// Work sessions completed since the last long break.
+    completed_cycles: u32,
+    /// Time spent in the current phase, or since the stopwatch was reset, before the timer was
+    /// last started.
+    elapsed: Duration,
+    /// When the timer was last started, `None` while paused.
+    started_at: Option<Instant>,
+
+    /// Size of the status text as last measured, it is centered in the ring.
+    text_size: (f32, f32),
+}
+
+impl Pomodoro {
+    pub fn new(config: PomodoroConfig) -> Result<Self, WidgetError> {
+        let work_duration = Duration::from_secs(config.work_duration * 60);
+
+        let text_config = TextConfig::new(
+            format_remaining(work_duration),
+            config.position.clone(),
+            config.font,
+        );
+        let widget = Text::new(text_config)?;
+
+        let (r, g, b) = config.work_color;
+        let work_color = Color::from_rgba8(r, g, b, 0xFF);
+        let (r, g, b) = config.break_color;
+        let break_color = Color::from_rgba8(r, g, b, 0xFF);
+
+        Ok(Self {
+            text_widget: widget,
+            position: config.position,
+            mode: config.mode,
+            keys: config.keys,
+            modifiers: ModifiersState::empty(),
+            ring_radius: config.ring_radius,
+            ring_width: config.ring_width,
+            work_color,
+            break_color,
+            work_duration,
+            short_break_duration: Duration::from_secs(config.short_break_duration * 60),
+            long_break_duration: Duration::from_secs(config.long_break_duration * 60),
+            cycles_before_long_break: config.cycles_before_long_break.max(1),
+            phase: Phase::Work,
+            completed_cycles: 0,
+            elapsed: Duration::ZERO,
+            started_at: None,
+            text_size: (0.0, 0.0),
+        })
+    }
+
+    fn phase_duration(&self) -> Duration {
+        match self.phase {
+            Phase::Work => self.work_duration,
+            Phase::ShortBreak => self.short_break_duration,
+            Phase::LongBreak => self.long_break_duration,
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        match self.started_at {
+            Some(started_at) => self.elapsed + started_at.elapsed(),
+            None => self.elapsed,
+        }
+    }
+
+    fn toggle(&mut self) {
+        match self.started_at.take() {
+            Some(started_at) => self.elapsed += started_at.elapsed(),
+            None => self.started_at = Some(Instant::now()),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = Phase::Work;
+        self.completed_cycles = 0;
+        self.elapsed = Duration::ZERO;
+        self.started_at = None;
+    }
+
+    /// Moves on to the next phase, leaving the timer paused.
+    fn skip(&mut self) {
+        self.phase = match self.phase {
+            Phase::Work => {
+                self.completed_cycles += 1;
+
+                if self.completed_cycles >= self.cycles_before_long_break {
+                    self.completed_cycles = 0;
+                    Phase::LongBreak
+                } else {
+                    Phase::ShortBreak
+                }
+            }
+            Phase::ShortBreak | Phase::LongBreak => Phase::Work,
+        };
+
+        self.elapsed = Duration::ZERO;
+        self.started_at = None;
+    }
+
+    /// Runs the action bound to `key`, returning whether there was one.
+    fn press(&mut self, key: &Key) -> bool {
+        if !(self.modifiers - ModifiersState::SHIFT).is_empty() {
+            return false;
+        }
+
+        let Some(text) = key.to_text() else {
+            return false;
+        };
+
+        if text.eq_ignore_ascii_case(&self.keys.toggle) {
+            self.toggle();
+        } else if text.eq_ignore_ascii_case(&self.keys.reset) {
+            self.reset();
+        } else if text.eq_ignore_ascii_case(&self.keys.skip) && self.mode == TimerMode::Pomodoro {
+            self.skip();
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// Text shown in the ring, and how far around the ring is filled.
+    fn status(&self) -> (String, f32) {
+        let elapsed = self.elapsed();
+
+        let (mut status, progress) = match self.mode {
+            TimerMode::Pomodoro => {
+                let duration = self.phase_duration();
+                let elapsed = elapsed.min(duration);
+                let progress = match duration.is_zero() {
+                    true => 1.0,
+                    false => elapsed.as_secs_f32() / duration.as_secs_f32(),
+                };
+
+                let status = format!(
+                    "{}\n{} {}/{}",
+                    format_remaining(duration - elapsed),
+                    self.phase.label(),
+                    self.completed_cycles,
+                    self.cycles_before_long_break
+                );
+
+                (status, progress)
+            }
+            TimerMode::Stopwatch => {
+                let progress = (elapsed.as_secs() % 60) as f32 / 60.0;
+
+                (format!("{}\nStopwatch", format_elapsed(elapsed)), progress)
+            }
+        };
+
+        if self.started_at.is_none() {
+            status.push_str(" (paused)");
+        }
+
+        (status, progress)
+    }
+
+    fn draw_ring(&self, buffer: &mut Pixmap, center: (f32, f32), progress: f32) {
+        let color = match (self.mode, self.phase) {
+            (TimerMode::Stopwatch, _) | (_, Phase::Work) => self.work_color,
+            (_, Phase::ShortBreak | Phase::LongBreak) => self.break_color,
+        };
+
+        let mut track_color = color;
+        track_color.set_alpha(0.2);
+
+        let stroke = Stroke {
+            width: self.ring_width,
+            line_cap: LineCap::Round,
+            ..Default::default()
+        };
+
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Default::default()
+        };
+
+        if let Some(track) = PathBuilder::from_circle(center.0, center.1, self.ring_radius) {
+            paint.set_color(track_color);
+            buffer.stroke_path(&track, &paint, &stroke, Transform::identity(), None);
+        }
+
+        // The arc starts at 12 o'clock and grows clockwise as the phase progresses.
+        let segments = (RING_SEGMENTS as f32 * progress).ceil() as usize;
+        if segments == 0 {
+            return;
+        }
+
+        let point = |fraction: f32| {
+            let angle = fraction * 2.0 * PI - PI / 2.0;
+            (
+                center.0 + self.ring_radius * angle.cos(),
+                center.1 + self.ring_radius * angle.sin(),
+            )
+        };
+
+        let mut path = PathBuilder::new();
+        let (x, y) = point(0.0);
+        path.move_to(x, y);
+
+        for segment in 1..=segments {
+            let fraction = (segment as f32 / RING_SEGMENTS as f32).min(progress);
+            let (x, y) = point(fraction);
+            path.line_to(x, y);
+        }
+
+        if let Some(arc) = path.finish() {
+            paint.set_color(color);
+            buffer.stroke_path(&arc, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+}
+
+impl Widget for Pomodoro {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let (status, _) = self.status();
+
+        self.text_widget.update_data(status);
+        self.text_size = self.text_widget.layout(space);
+
+        let diameter = self.ring_radius * 2.0 + self.ring_width;
+        Some((diameter, diameter))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let (_, progress) = self.status();
+
+        let diameter = self.ring_radius * 2.0 + self.ring_width;
+        let center = (x as f32 + diameter / 2.0, y as f32 + diameter / 2.0);
+        self.draw_ring(buffer, center, progress);
+
+        let (width, height) = self.text_size;
+        self.text_widget.paint(
+            buffer,
+            (center.0 - width / 2.0) as i32,
+            (center.1 - height / 2.0) as i32,
+        );
+    }
+
+    fn on_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers.state();
+                false
+            }
+            WindowEvent::KeyboardInput { event, .. } if event.state.is_pressed() => {
+                self.press(&event.logical_key)
+            }
+            _ => false,
+        }
+    }
+
+    fn on_tick(&mut self, _now: Instant) {
+        if self.mode == TimerMode::Pomodoro && self.elapsed() >= self.phase_duration() {
+            self.skip();
+        }
+    }
+
+    /// The next time the shown second changes, which is also when a phase ends. Nothing happens
+    /// while paused.
+    fn next_tick(&self) -> Option<Instant> {
+        let started_at = self.started_at?;
+        let next_second = Duration::from_secs(self.elapsed().as_secs() + 1);
+
+        Some(started_at + next_second.saturating_sub(self.elapsed))
+    }
+
+    fn focusable(&self) -> bool {
+        true
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+
+    match seconds / 3600 {
+        0 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{hours}:{:02}:{:02}", seconds / 60 % 60, seconds % 60),
+    }
+}
+
+fn format_remaining(remaining: Duration) -> String {
+    // Round up so the timer shows 00:01 until the very last second has passed.
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn pomodoro(mode: &str) -> Pomodoro {
+        let config = json!({
+            "mode": mode,
+            "work_duration": 1,
+            "short_break_duration": 1,
+            "long_break_duration": 1,
+            "cycles_before_long_break": 2,
+            "line_height": 20.0,
+            "font_family": null,
+            "font_size": 16.0,
+            "font_weight": 400,
+        });
+
+        Pomodoro::new(serde_json::from_value(config).unwrap()).unwrap()
+    }
+
+    /// Pretends the timer was started `seconds` ago.
+    fn run_for(pomodoro: &mut Pomodoro, seconds: f32) {
+        pomodoro.started_at = Some(Instant::now() - Duration::from_secs_f32(seconds));
+    }
+
+    #[test]
+    fn paused_timer_does_not_tick() {
+        let mut pomodoro = pomodoro("Pomodoro");
+        assert_eq!(pomodoro.next_tick(), None);
+
+        pomodoro.toggle();
+        assert!(pomodoro.next_tick().is_some());
+
+        pomodoro.toggle();
+        assert_eq!(pomodoro.next_tick(), None);
+    }
+
+    #[test]
+    fn ticks_when_the_shown_second_changes() {
+        let mut pomodoro = pomodoro("Pomodoro");
+        pomodoro.elapsed = Duration::from_millis(500);
+        run_for(&mut pomodoro, 0.25);
+
+        let started_at = pomodoro.started_at.unwrap();
+        assert_eq!(
+            pomodoro.next_tick(),
+            Some(started_at + Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn phases_follow_each_other() {
+        let mut pomodoro = pomodoro("Pomodoro");
+
+        pomodoro.skip();
+        assert_eq!(pomodoro.phase, Phase::ShortBreak);
+        pomodoro.skip();
+        assert_eq!(pomodoro.phase, Phase::Work);
+        pomodoro.skip();
+        assert_eq!(pomodoro.phase, Phase::LongBreak);
+
+        // A finished phase moves on by itself and waits to be started again.
+        run_for(&mut pomodoro, 61.0);
+        pomodoro.on_tick(Instant::now());
+        assert_eq!(pomodoro.phase, Phase::Work);
+        assert_eq!(pomodoro.started_at, None);
+
+        pomodoro.skip();
+        pomodoro.reset();
+        assert_eq!(pomodoro.phase, Phase::Work);
+        assert_eq!(pomodoro.completed_cycles, 0);
+    }
+
+    #[test]
+    fn stopwatch_counts_up_until_reset() {
+        let mut pomodoro = pomodoro("Stopwatch");
+        run_for(&mut pomodoro, 3725.5);
+        pomodoro.on_tick(Instant::now());
+
+        let (status, progress) = pomodoro.status();
+        assert_eq!(status, "1:02:05\nStopwatch");
+        assert_eq!(progress, 5.0 / 60.0);
+
+        pomodoro.toggle();
+        assert!(pomodoro.status().0.ends_with("(paused)"));
+
+        pomodoro.reset();
+        assert_eq!(pomodoro.status().0, "00:00\nStopwatch (paused)");
+    }
+
+    #[test]
+    fn remaining_time_is_rounded_up() {
+        assert_eq!(format_remaining(Duration::from_millis(1)), "00:01");
+        assert_eq!(format_remaining(Duration::from_secs(25 * 60)), "25:00");
+        assert_eq!(format_elapsed(Duration::from_millis(59_999)), "00:59");
+    }
+
+    #[test]
+    fn keys_with_modifiers_are_ignored() {
+        let mut pomodoro = pomodoro("Pomodoro");
+        let key = |text: &str| Key::Character(text.into());
+
+        assert!(pomodoro.press(&key(" ")));
+        assert!(pomodoro.started_at.is_some());
+
+        pomodoro.on_event(&WindowEvent::ModifiersChanged(
+            ModifiersState::CONTROL.into(),
+        ));
+        assert!(!pomodoro.press(&key("s")));
+        assert_eq!(pomodoro.phase, Phase::Work);
+
+        pomodoro.on_event(&WindowEvent::ModifiersChanged(ModifiersState::SHIFT.into()));
+        assert!(pomodoro.press(&key("S")));
+        assert_eq!(pomodoro.phase, Phase::ShortBreak);
+
+        assert!(!pomodoro.press(&key("x")));
+    }
+
+    #[test]
+    fn stopwatch_has_no_phases_to_skip() {
+        let mut pomodoro = pomodoro("Stopwatch");
+
+        assert!(!pomodoro.press(&Key::Character("s".into())));
+        run_for(&mut pomodoro, 3600.0);
+        pomodoro.on_tick(Instant::now());
+        assert_eq!(pomodoro.phase, Phase::Work);
+    }
+}
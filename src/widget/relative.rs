@@ -0,0 +1,311 @@
+use tiny_skia::Paint;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use tiny_skia::Stroke;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::window::Window;
+
+use crate::config;
+use crate::config::WidgetEntry;
+use crate::layout;
+use crate::layout::Align;
+use crate::layout::LayoutError;
+use crate::layout::Length;
+use crate::layout::Side;
+use crate::render::DrawError;
+use crate::render::Drawable;
+
+use super::rounded_rect;
+use super::Bounds;
+use super::Position;
+use super::Widget;
+
+/// How a widget is placed next to another one.
+struct Relation {
+    /// Index of the other widget, which is always placed first.
+    target: usize,
+    side: Side,
+    gap: Length,
+    align: Align,
+}
+
+impl Relation {
+    /// Computes the top-left corner of a widget of the given size next to `target`.
+    fn resolve(
+        &self,
+        window: PhysicalSize<u32>,
+        target: Bounds,
+        (width, height): (f32, f32),
+    ) -> (f32, f32) {
+        let align = self.align.fraction();
+
+        match self.side {
+            Side::Above | Side::Below => {
+                let gap = self.gap.to_pixels(window.height as f32, window);
+                let x = target.x + align * (target.width - width);
+
+                match self.side {
+                    Side::Above => (x, target.y - gap - height),
+                    _ => (x, target.y + target.height + gap),
+                }
+            }
+
+            Side::LeftOf | Side::RightOf => {
+                let gap = self.gap.to_pixels(window.width as f32, window);
+                let y = target.y + align * (target.height - height);
+
+                match self.side {
+                    Side::LeftOf => (target.x - gap - width, y),
+                    _ => (target.x + target.width + gap, y),
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the widgets of the window placed relative to each other next to each other.
+pub struct Relations {
+    relations: Vec<Option<Relation>>,
+    /// Indices of the widgets in the order they are placed in, each one after the widget it is
+    /// placed relative to.
+    order: Vec<usize>,
+    /// Area each widget covered in the window on the last frame.
+    areas: Vec<Option<Bounds>>,
+}
+
+impl Relations {
+    /// Finds the widget each one is placed relative to, failing when an id is missing, used twice,
+    /// widgets depend on each other in a loop or a widget inside another one is placed relative to
+    /// a widget. The widgets keep the order they are listed in.
+    pub fn arrange(
+        entries: Vec<WidgetEntry>,
+    ) -> Result<(Vec<config::Widget>, Relations), LayoutError> {
+        for entry in &entries {
+            check_children(&entry.widget)?;
+        }
+
+        let ids: Vec<_> = entries.iter().map(|entry| entry.id.as_deref()).collect();
+        let targets: Vec<_> = entries
+            .iter()
+            .map(|entry| match entry.widget.position() {
+                Position::RelativeTo { relative_to, .. } => Some(relative_to.as_str()),
+                _ => None,
+            })
+            .collect();
+
+        let (order, targets) = layout::dependency_order(&ids, &targets)?;
+
+        let (widgets, relations) = entries
+            .into_iter()
+            .zip(targets)
+            .map(|(entry, target)| {
+                let relation = match (entry.widget.position(), target) {
+                    (
+                        Position::RelativeTo {
+                            side, gap, align, ..
+                        },
+                        Some(target),
+                    ) => Some(Relation {
+                        target,
+                        side: *side,
+                        gap: *gap,
+                        align: *align,
+                    }),
+                    _ => None,
+                };
+
+                (entry.widget, relation)
+            })
+            .unzip();
+
+        Ok((widgets, Relations::new(relations, order)))
+    }
+
+    fn new(relations: Vec<Option<Relation>>, order: Vec<usize>) -> Self {
+        Self {
+            areas: vec![None; relations.len()],
+            relations,
+            order,
+        }
+    }
+
+    /// Number of widgets in the window.
+    pub fn len(&self) -> usize {
+        self.areas.len()
+    }
+
+    /// Area the widget at `index` covered in the window on the last frame.
+    pub fn bounds(&self, index: usize) -> Option<Bounds> {
+        self.areas.get(index).copied().flatten()
+    }
+
+    fn draw(
+        &mut self,
+        widgets: &mut [Box<dyn Widget>],
+        focused: Option<usize>,
+        window: &Window,
+        buffer: &mut Pixmap,
+    ) {
+        let window = window.inner_size();
+
+        // Every widget is placed before any is drawn, after the widget it follows.
+        for &index in &self.order {
+            let widget = &mut widgets[index];
+            let space = widget.position().space(window);
+
+            self.areas[index] = widget.measure(window, space).map(|size| {
+                let target = self.relations[index]
+                    .as_ref()
+                    .and_then(|relation| Some((relation, self.areas[relation.target]?)));
+
+                let origin = match target {
+                    Some((relation, target)) => {
+                        let (x, y) = relation.resolve(window, target, size);
+                        (x.round() as i32, y.round() as i32)
+                    }
+                    // Widgets following one with nothing to draw stay where they would go on
+                    // their own.
+                    None => widget.position().resolve(window, size),
+                };
+
+                Bounds::new(origin, size)
+            });
+        }
+
+        for (widget, area) in widgets.iter_mut().zip(&self.areas) {
+            if let Some(area) = area {
+                widget.draw_at(window, buffer, (area.x as i32, area.y as i32));
+            }
+        }
+
+        if let Some(area) = focused.and_then(|index| self.bounds(index)) {
+            draw_focus(buffer, area);
+        }
+    }
+}
+
+/// Outlines the focused widget.
+fn draw_focus(buffer: &mut Pixmap, area: Bounds) {
+    const GAP: f32 = 4.0;
+
+    let outline = Rect::from_xywh(
+        area.x - GAP,
+        area.y - GAP,
+        area.width + GAP * 2.0,
+        area.height + GAP * 2.0,
+    );
+    let Some(path) = outline.and_then(|outline| rounded_rect(outline, GAP * 2.0)) else {
+        return;
+    };
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    paint.set_color_rgba8(255, 255, 255, 160);
+
+    let stroke = Stroke {
+        width: 2.0,
+        ..Default::default()
+    };
+    buffer.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+}
+
+/// Fails when a widget inside `widget`, at any depth, is placed relative to another one.
+fn check_children(widget: &config::Widget) -> Result<(), LayoutError> {
+    for child in widget.children() {
+        if let Position::RelativeTo { relative_to, .. } = child.position() {
+            return Err(LayoutError::NestedRelation(relative_to.clone()));
+        }
+
+        check_children(child)?;
+    }
+
+    Ok(())
+}
+
+/// The widgets of the window, drawn in the order they are listed in.
+pub struct Scene<'a> {
+    pub widgets: &'a mut [Box<dyn Widget>],
+    pub relations: &'a mut Relations,
+    /// Widget to outline as focused.
+    pub focused: Option<usize>,
+}
+
+impl Drawable for Scene<'_> {
+    fn draw(&mut self, window: &Window, buffer: &mut Pixmap) -> Result<(), DrawError> {
+        self.relations
+            .draw(self.widgets, self.focused, window, buffer);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serde_json::Value;
+
+    use super::*;
+
+    fn rectangle(id: Option<&str>, position: Value) -> Value {
+        json!({
+            "id": id,
+            "widget": "Rectangle",
+            "width": 10.0,
+            "height": 10.0,
+            "position": position,
+        })
+    }
+
+    fn below(id: &str) -> Value {
+        json!({ "position": "RelativeTo", "relative_to": id })
+    }
+
+    fn arrange(entries: Value) -> Result<(Vec<config::Widget>, Relations), LayoutError> {
+        Relations::arrange(serde_json::from_value(entries).unwrap())
+    }
+
+    #[test]
+    fn widgets_keep_their_listed_order() {
+        let (widgets, relations) = arrange(json!([
+            rectangle(Some("date"), below("clock")),
+            rectangle(Some("clock"), json!({ "position": "Center" })),
+        ]))
+        .unwrap();
+
+        assert_eq!(widgets.len(), 2);
+        assert!(matches!(
+            widgets[0].position(),
+            Position::RelativeTo { relative_to, .. } if relative_to == "clock"
+        ));
+        assert_eq!(relations.order, [1, 0]);
+        assert_eq!(
+            relations.relations[0]
+                .as_ref()
+                .map(|relation| relation.target),
+            Some(1)
+        );
+        assert!(relations.relations[1].is_none());
+    }
+
+    #[test]
+    fn nested_relations_are_rejected() {
+        let entries = json!([
+            rectangle(Some("clock"), json!({ "position": "Center" })),
+            {
+                "widget": "Column",
+                "children": [{
+                    "widget": "Panel",
+                    "child": rectangle(None, below("clock")),
+                }],
+            },
+        ]);
+
+        assert!(matches!(
+            arrange(entries),
+            Err(LayoutError::NestedRelation(id)) if id == "clock"
+        ));
+    }
+}
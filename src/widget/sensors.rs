@@ -0,0 +1,461 @@
+use std::ffi::CString;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cosmic_text::Color;
+use spin::RwLock;
+use tiny_skia::Pixmap;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::DiskConfig;
+use crate::config::MeterConfig;
+use crate::config::TemperatureConfig;
+use crate::config::TextConfig;
+use crate::config::Thresholds;
+
+use super::human_bytes;
+use super::text::Text;
+use super::threshold_color;
+use super::Position;
+use super::Row;
+use super::Rows;
+use super::Widget;
+use super::WidgetError;
+
+/// Latest reading of each configured mount point or sensor, `None` when it couldn't be read.
+type Readings<T> = Arc<RwLock<Vec<(String, Option<T>)>>>;
+
+#[derive(Debug, Clone, Copy)]
+struct DiskUsage {
+    used: f64,
+    free: f64,
+    total: f64,
+}
+
+impl DiskUsage {
+    /// Used fraction of the space available to unprivileged users, the same way `df` shows it.
+    fn fraction(&self) -> f32 {
+        match self.used + self.free > 0.0 {
+            true => (self.used / (self.used + self.free)) as f32,
+            false => 0.0,
+        }
+    }
+}
+
+pub struct Disk {
+    text_widget: Text,
+    text_color: Color,
+    position: Position,
+    format: String,
+    bar: Option<MeterConfig>,
+    thresholds: Thresholds,
+    usage: Readings<DiskUsage>,
+    rows: Rows,
+}
+
+impl Disk {
+    pub fn new(event_loop: &EventLoop<()>, config: DiskConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.font.color;
+        let text_color = Color::rgba(r, g, b, a);
+
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let usage = Arc::new(RwLock::new(read_disks(&config.mount_points)));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let usage = usage.clone();
+            let mount_points = config.mount_points;
+            let interval = Duration::from_secs(config.interval.max(1));
+
+            std::thread::spawn(move || loop {
+                std::thread::sleep(interval);
+
+                *usage.write() = read_disks(&mount_points);
+                event_loop_proxy.send_event(()).unwrap();
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            text_color,
+            position: config.position,
+            format: config.format,
+            bar: config.bar,
+            thresholds: config.thresholds,
+            usage,
+            rows: Rows::default(),
+        })
+    }
+}
+
+impl Widget for Disk {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let rows: Vec<Row> = self
+            .usage
+            .read()
+            .iter()
+            .map(|(mount, usage)| match usage {
+                Some(usage) => {
+                    let percent = usage.fraction() * 100.0;
+
+                    Row {
+                        text: self
+                            .format
+                            .replace("{mount}", mount)
+                            .replace("{used}", &human_bytes(usage.used))
+                            .replace("{free}", &human_bytes(usage.free))
+                            .replace("{total}", &human_bytes(usage.total))
+                            .replace("{percent}", &format!("{percent:.0}")),
+                        color: threshold_color(&self.thresholds, percent, self.text_color),
+                        fraction: Some(usage.fraction()),
+                    }
+                }
+
+                None => Row {
+                    text: format!("{mount} unavailable"),
+                    color: self.text_color,
+                    fraction: None,
+                },
+            })
+            .collect();
+
+        self.rows
+            .measure(&mut self.text_widget, rows, space, self.bar.as_ref())
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.rows
+            .draw(&mut self.text_widget, buffer, origin, self.bar.as_ref());
+    }
+}
+
+pub struct Temperature {
+    text_widget: Text,
+    text_color: Color,
+    position: Position,
+    format: String,
+    thresholds: Thresholds,
+    temperatures: Readings<f32>,
+    rows: Rows,
+}
+
+impl Temperature {
+    pub fn new(event_loop: &EventLoop<()>, config: TemperatureConfig) -> Result<Self, WidgetError> {
+        let (r, g, b, a) = config.font.color;
+        let text_color = Color::rgba(r, g, b, a);
+
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let root = PathBuf::from(config.root);
+        let temperatures = Arc::new(RwLock::new(read_temperatures(&root, &config.sensors)));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let temperatures = temperatures.clone();
+            let sensors = config.sensors;
+            let interval = Duration::from_secs(config.interval.max(1));
+
+            std::thread::spawn(move || loop {
+                std::thread::sleep(interval);
+
+                *temperatures.write() = read_temperatures(&root, &sensors);
+                event_loop_proxy.send_event(()).unwrap();
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            text_color,
+            position: config.position,
+            format: config.format,
+            thresholds: config.thresholds,
+            temperatures,
+            rows: Rows::default(),
+        })
+    }
+}
+
+impl Widget for Temperature {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let rows: Vec<Row> = self
+            .temperatures
+            .read()
+            .iter()
+            .map(|(label, temperature)| match temperature {
+                Some(temperature) => Row {
+                    text: self
+                        .format
+                        .replace("{label}", label)
+                        .replace("{temperature}", &format!("{temperature:.0}")),
+                    color: threshold_color(&self.thresholds, *temperature, self.text_color),
+                    fraction: None,
+                },
+
+                None => Row {
+                    text: format!("{label} unavailable"),
+                    color: self.text_color,
+                    fraction: None,
+                },
+            })
+            .collect();
+
+        self.rows.measure(&mut self.text_widget, rows, space, None)
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, origin: (i32, i32)) {
+        self.rows.draw(&mut self.text_widget, buffer, origin, None);
+    }
+}
+
+fn read_disks(mount_points: &[String]) -> Vec<(String, Option<DiskUsage>)> {
+    mount_points
+        .iter()
+        .map(|mount| (mount.clone(), statvfs(mount)))
+        .collect()
+}
+
+fn statvfs(mount: &str) -> Option<DiskUsage> {
+    let path = CString::new(mount).ok()?;
+
+    // SAFETY: `statvfs` is plain old data, so zeroed memory is a valid value for it to fill.
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+
+    // SAFETY: `path` is a valid C string and `stat` is a valid pointer for the whole call.
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    let block_size = stat.f_frsize as f64;
+
+    Some(DiskUsage {
+        used: stat.f_blocks.saturating_sub(stat.f_bfree) as f64 * block_size,
+        free: stat.f_bavail as f64 * block_size,
+        total: stat.f_blocks as f64 * block_size,
+    })
+}
+
+/// Reads every sensor under `root`, then picks the ones matching the configured labels.
+fn read_temperatures(root: &Path, sensors: &[String]) -> Vec<(String, Option<f32>)> {
+    let readings = read_hwmon(&root.join("hwmon"))
+        .into_iter()
+        .chain(read_thermal_zones(&root.join("thermal")))
+        .collect::<Vec<_>>();
+
+    sensors
+        .iter()
+        .map(|sensor| {
+            let temperature = readings.iter().find_map(|(labels, temperature)| {
+                labels
+                    .iter()
+                    .any(|label| label.eq_ignore_ascii_case(sensor))
+                    .then_some(*temperature)
+            });
+
+            (sensor.clone(), temperature)
+        })
+        .collect()
+}
+
+/// Reads a temperature in millidegrees Celsius.
+fn read_millidegrees(path: &Path) -> Option<f32> {
+    let value = std::fs::read_to_string(path).ok()?;
+    let millidegrees = value.trim().parse::<f32>().ok()?;
+
+    Some(millidegrees / 1000.0)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+fn sorted_entries(directory: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+    entries
+}
+
+/// Reads `hwmon*/temp*_input`, labelled by `temp*_label`, the chip name and `chip/label`.
+fn read_hwmon(root: &Path) -> Vec<(Vec<String>, f32)> {
+    let mut readings = Vec::new();
+
+    for device in sorted_entries(root) {
+        let chip = read_trimmed(&device.join("name"));
+
+        for input in sorted_entries(&device) {
+            let Some(file_name) = input.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            let Some(sensor) = file_name
+                .strip_prefix("temp")
+                .and_then(|name| name.strip_suffix("_input"))
+            else {
+                continue;
+            };
+
+            let Some(temperature) = read_millidegrees(&input) else {
+                continue;
+            };
+
+            let label = read_trimmed(&device.join(format!("temp{sensor}_label")));
+
+            let mut labels = Vec::new();
+            if let Some(label) = &label {
+                labels.push(label.clone());
+            }
+            if let Some(chip) = &chip {
+                labels.push(chip.clone());
+                labels.push(format!("{chip}/{}", label.as_deref().unwrap_or(file_name)));
+            }
+
+            readings.push((labels, temperature));
+        }
+    }
+
+    readings
+}
+
+/// Reads `thermal_zone*/temp`, labelled by the zone `type`.
+fn read_thermal_zones(root: &Path) -> Vec<(Vec<String>, f32)> {
+    sorted_entries(root)
+        .into_iter()
+        .filter(|zone| {
+            zone.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("thermal_zone"))
+        })
+        .filter_map(|zone| {
+            let kind = read_trimmed(&zone.join("type"))?;
+            let temperature = read_millidegrees(&zone.join("temp"))?;
+
+            Some((vec![kind], temperature))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Thresholds;
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    /// A sysfs class root holding the given files, paths are relative to it.
+    fn sysfs(test: &str, files: &[(&str, &str)]) -> TempDir {
+        let root = TempDir::new(&format!("sensors-{test}"));
+
+        for (path, contents) in files {
+            let path = root.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+
+        root
+    }
+
+    fn sensors(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn hwmon_sensors_match_their_label_or_chip() {
+        let root = sysfs(
+            "hwmon",
+            &[
+                ("hwmon/hwmon0/name", "coretemp\n"),
+                ("hwmon/hwmon0/temp1_input", "45500\n"),
+                ("hwmon/hwmon0/temp1_label", "Package id 0\n"),
+                ("hwmon/hwmon0/temp2_input", "41000\n"),
+                ("hwmon/hwmon1/name", "nvme\n"),
+                ("hwmon/hwmon1/temp1_input", "-2000\n"),
+            ],
+        );
+
+        let temperatures = read_temperatures(
+            &root,
+            &sensors(&["package ID 0", "coretemp/temp2_input", "nvme", "missing"]),
+        );
+
+        assert_eq!(
+            temperatures,
+            [
+                ("package ID 0".to_string(), Some(45.5)),
+                ("coretemp/temp2_input".to_string(), Some(41.0)),
+                ("nvme".to_string(), Some(-2.0)),
+                ("missing".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn thermal_zones_are_used_when_hwmon_has_no_match() {
+        let root = sysfs(
+            "thermal",
+            &[
+                ("hwmon/hwmon0/name", "acpitz\n"),
+                ("hwmon/hwmon0/temp1_input", "not a number\n"),
+                ("thermal/thermal_zone0/type", "x86_pkg_temp\n"),
+                ("thermal/thermal_zone0/temp", "52000\n"),
+                ("thermal/cooling_device0/type", "Processor\n"),
+                ("thermal/cooling_device0/temp", "1000\n"),
+            ],
+        );
+
+        let temperatures =
+            read_temperatures(&root, &sensors(&["x86_pkg_temp", "acpitz", "Processor"]));
+
+        assert_eq!(
+            temperatures,
+            [
+                ("x86_pkg_temp".to_string(), Some(52.0)),
+                // Unreadable inputs and devices that aren't thermal zones are skipped.
+                ("acpitz".to_string(), None),
+                ("Processor".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn temperatures_are_colored_past_their_thresholds() {
+        let thresholds = Thresholds::new(70.0, 90.0);
+        let text = Color::rgba(255, 255, 255, 255);
+
+        assert_eq!(threshold_color(&thresholds, 69.9, text), text);
+        assert_eq!(
+            threshold_color(&thresholds, 70.0, text),
+            Color::rgba(241, 196, 15, 200)
+        );
+        assert_eq!(
+            threshold_color(&thresholds, 95.0, text),
+            Color::rgba(231, 76, 60, 200)
+        );
+
+        // Thresholds going down color low values instead, e.g. a battery running out.
+        let thresholds = Thresholds::new(20.0, 10.0);
+        assert_eq!(threshold_color(&thresholds, 50.0, text), text);
+        assert_eq!(
+            threshold_color(&thresholds, 10.0, text),
+            Color::rgba(231, 76, 60, 200)
+        );
+    }
+}
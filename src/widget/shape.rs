@@ -0,0 +1,266 @@
+use tiny_skia::Color;
+use tiny_skia::FillRule;
+use tiny_skia::GradientStop;
+use tiny_skia::LineCap;
+use tiny_skia::LinearGradient;
+use tiny_skia::Paint;
+use tiny_skia::Path;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Point;
+use tiny_skia::RadialGradient;
+use tiny_skia::Rect;
+use tiny_skia::Shader;
+use tiny_skia::SpreadMode;
+use tiny_skia::Stroke;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+
+use crate::config::CircleConfig;
+use crate::config::Fill;
+use crate::config::LineConfig;
+use crate::config::RectangleConfig;
+use crate::config::StrokeConfig;
+
+use super::rounded_rect;
+use super::Position;
+use super::Widget;
+
+pub struct Rectangle {
+    width: f32,
+    height: f32,
+    corner_radius: f32,
+    fill: Option<Fill>,
+    stroke: Option<StrokeConfig>,
+    position: Position,
+}
+
+impl Rectangle {
+    pub fn new(config: RectangleConfig) -> Self {
+        Self {
+            width: config.width,
+            height: config.height,
+            corner_radius: config.corner_radius,
+            fill: config.fill,
+            stroke: config.stroke,
+            position: config.position,
+        }
+    }
+}
+
+impl Widget for Rectangle {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, _space: (f32, f32)) -> Option<(f32, f32)> {
+        Some((self.width, self.height))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let Some(area) = Rect::from_xywh(x as f32, y as f32, self.width, self.height) else {
+            return;
+        };
+
+        if let Some(path) = rounded_rect(area, self.corner_radius) {
+            draw_shape(
+                buffer,
+                &path,
+                area,
+                self.fill.as_ref(),
+                self.stroke.as_ref(),
+            );
+        }
+    }
+}
+
+pub struct Circle {
+    radius: f32,
+    fill: Option<Fill>,
+    stroke: Option<StrokeConfig>,
+    position: Position,
+}
+
+impl Circle {
+    pub fn new(config: CircleConfig) -> Self {
+        Self {
+            radius: config.radius,
+            fill: config.fill,
+            stroke: config.stroke,
+            position: config.position,
+        }
+    }
+}
+
+impl Widget for Circle {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, _space: (f32, f32)) -> Option<(f32, f32)> {
+        let size = self.radius * 2.0;
+        Some((size, size))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let size = self.radius * 2.0;
+        let area = Rect::from_xywh(x as f32, y as f32, size, size);
+        let path =
+            PathBuilder::from_circle(x as f32 + self.radius, y as f32 + self.radius, self.radius);
+
+        if let Some((area, path)) = area.zip(path) {
+            draw_shape(
+                buffer,
+                &path,
+                area,
+                self.fill.as_ref(),
+                self.stroke.as_ref(),
+            );
+        }
+    }
+}
+
+pub struct Line {
+    length: f32,
+    angle: f32,
+    stroke: StrokeConfig,
+    position: Position,
+}
+
+impl Line {
+    pub fn new(config: LineConfig) -> Self {
+        Self {
+            length: config.length,
+            angle: config.angle,
+            stroke: config.stroke,
+            position: config.position,
+        }
+    }
+
+    /// How far the line goes along each axis.
+    fn extent(&self) -> (f32, f32) {
+        let (sin, cos) = self.angle.to_radians().sin_cos();
+        (self.length * cos, self.length * sin)
+    }
+}
+
+impl Widget for Line {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, _space: (f32, f32)) -> Option<(f32, f32)> {
+        let (dx, dy) = self.extent();
+
+        // The line is placed by the box around it, a horizontal or vertical one is still as thick
+        // as its stroke.
+        Some((
+            dx.abs().max(self.stroke.width),
+            dy.abs().max(self.stroke.width),
+        ))
+    }
+
+    fn draw_at(&mut self, window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let (dx, dy) = self.extent();
+        let Some((width, height)) = self.measure(window, (0.0, 0.0)) else {
+            return;
+        };
+
+        let center = (x as f32 + width / 2.0, y as f32 + height / 2.0);
+        let mut path = PathBuilder::new();
+        path.move_to(center.0 - dx / 2.0, center.1 - dy / 2.0);
+        path.line_to(center.0 + dx / 2.0, center.1 + dy / 2.0);
+
+        if let Some(path) = path.finish() {
+            let (paint, stroke) = stroke_paint(&self.stroke);
+            buffer.stroke_path(&path, &paint, &stroke, Transform::identity(), None);
+        }
+    }
+}
+
+/// Fills `path` and draws its outline, gradients are laid out over `area`.
+pub(super) fn draw_shape(
+    buffer: &mut Pixmap,
+    path: &Path,
+    area: Rect,
+    fill: Option<&Fill>,
+    stroke: Option<&StrokeConfig>,
+) {
+    if let Some(paint) = fill.and_then(|fill| fill_paint(fill, area)) {
+        buffer.fill_path(path, &paint, FillRule::Winding, Transform::identity(), None);
+    }
+
+    if let Some(stroke) = stroke {
+        let (paint, stroke) = stroke_paint(stroke);
+        buffer.stroke_path(path, &paint, &stroke, Transform::identity(), None);
+    }
+}
+
+/// Paint that covers `area` the way `fill` describes.
+pub(super) fn fill_paint(fill: &Fill, area: Rect) -> Option<Paint<'static>> {
+    let center = Point::from_xy(
+        area.x() + area.width() / 2.0,
+        area.y() + area.height() / 2.0,
+    );
+
+    let shader = match fill {
+        Fill::Color((r, g, b, a)) => Shader::SolidColor(Color::from_rgba8(*r, *g, *b, *a)),
+
+        Fill::LinearGradient { angle, stops } => {
+            // The gradient runs through the center, long enough to reach the corners.
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let extent = (area.width() * cos.abs() + area.height() * sin.abs()) / 2.0;
+
+            LinearGradient::new(
+                Point::from_xy(center.x - cos * extent, center.y - sin * extent),
+                Point::from_xy(center.x + cos * extent, center.y + sin * extent),
+                gradient_stops(stops),
+                SpreadMode::Pad,
+                Transform::identity(),
+            )?
+        }
+
+        Fill::RadialGradient { stops } => RadialGradient::new(
+            center,
+            center,
+            area.width().hypot(area.height()) / 2.0,
+            gradient_stops(stops),
+            SpreadMode::Pad,
+            Transform::identity(),
+        )?,
+    };
+
+    Some(Paint {
+        shader,
+        anti_alias: true,
+        ..Default::default()
+    })
+}
+
+fn gradient_stops(stops: &[crate::config::GradientStop]) -> Vec<GradientStop> {
+    stops
+        .iter()
+        .map(|stop| {
+            let (r, g, b, a) = stop.color;
+            GradientStop::new(stop.offset, Color::from_rgba8(r, g, b, a))
+        })
+        .collect()
+}
+
+fn stroke_paint(stroke: &StrokeConfig) -> (Paint<'static>, Stroke) {
+    let (r, g, b, a) = stroke.color;
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    paint.set_color_rgba8(r, g, b, a);
+
+    let stroke = Stroke {
+        width: stroke.width,
+        line_cap: LineCap::Round,
+        ..Default::default()
+    };
+
+    (paint, stroke)
+}
@@ -0,0 +1,519 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use spin::RwLock;
+use tiny_skia::Color;
+use tiny_skia::Paint;
+use tiny_skia::Pixmap;
+use tiny_skia::Rect;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::GraphStyle;
+use crate::config::MeterConfig;
+use crate::config::MonitorDisplay;
+use crate::config::SystemMonitorConfig;
+use crate::config::TextConfig;
+
+use super::graph::Chart;
+use super::graph::History;
+use super::human_bytes;
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// Space between the text and the bar or sparkline below it.
+const METER_SPACING: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// CPU usage from `/proc/stat`, placeholders: `{usage}`.
+    Cpu,
+    /// Memory usage from `/proc/meminfo`, placeholders: `{used}`, `{total}`, `{percent}`.
+    Memory,
+    /// Load averages from `/proc/loadavg`, placeholders: `{1m}`, `{5m}`, `{15m}`.
+    LoadAverage,
+    /// Time since boot from `/proc/uptime`, placeholders: `{uptime}`.
+    Uptime,
+}
+
+impl Metric {
+    fn default_format(&self) -> &'static str {
+        match self {
+            Metric::Cpu => "CPU {usage}%",
+            Metric::Memory => "MEM {used} / {total}",
+            Metric::LoadAverage => "LOAD {1m} {5m} {15m}",
+            Metric::Uptime => "UP {uptime}",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct Reading {
+    /// Values for the format placeholders.
+    fields: Vec<(&'static str, String)>,
+    /// How "full" the metric is, between 0 and 1, for metrics that can be drawn as a meter.
+    pub(super) fraction: Option<f32>,
+}
+
+#[derive(Debug)]
+struct MonitorState {
+    reading: Option<Reading>,
+    history: History,
+}
+
+pub struct SystemMonitor {
+    text_widget: Text,
+    position: Position,
+    format: String,
+    display: MonitorDisplay,
+    state: Arc<RwLock<MonitorState>>,
+    /// Height of the text and the fraction shown by the meter, if any, as last measured.
+    measured: (f32, Option<f32>),
+}
+
+impl SystemMonitor {
+    pub fn new(
+        event_loop: &EventLoop<()>,
+        metric: Metric,
+        config: SystemMonitorConfig,
+    ) -> Result<Self, WidgetError> {
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let history_size = match &config.display {
+            MonitorDisplay::Sparkline(meter) => meter.samples.max(2),
+            MonitorDisplay::Text | MonitorDisplay::Bar(_) => 1,
+        };
+
+        let state = Arc::new(RwLock::new(MonitorState {
+            reading: None,
+            history: History::new(history_size),
+        }));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let state = state.clone();
+            let interval = Duration::from_secs(config.interval.max(1));
+            let mut sampler = Sampler::new(metric, PathBuf::from(config.root));
+
+            std::thread::spawn(move || loop {
+                let reading = sampler.sample();
+
+                {
+                    let mut state = state.write();
+
+                    if let Some(fraction) = reading.as_ref().and_then(|reading| reading.fraction) {
+                        state.history.push(fraction);
+                    }
+
+                    state.reading = reading;
+                }
+
+                event_loop_proxy.send_event(()).unwrap();
+                std::thread::sleep(interval);
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            position: config.position,
+            format: config
+                .format
+                .unwrap_or_else(|| metric.default_format().to_string()),
+            display: config.display,
+            state,
+            measured: (0.0, None),
+        })
+    }
+}
+
+impl Widget for SystemMonitor {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let state = self.state.read();
+        let reading = state.reading.as_ref()?;
+
+        let text = reading
+            .fields
+            .iter()
+            .fold(self.format.clone(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            });
+        self.text_widget.update_data(text);
+
+        let (text_width, text_height) = self.text_widget.layout(space);
+
+        // Metrics without a fraction (like uptime) can only be shown as text.
+        let fraction = match self.display {
+            MonitorDisplay::Bar(_) | MonitorDisplay::Sparkline(_) => reading.fraction,
+            MonitorDisplay::Text => None,
+        };
+        self.measured = (text_height, fraction);
+
+        match (&self.display, fraction) {
+            (MonitorDisplay::Bar(meter) | MonitorDisplay::Sparkline(meter), Some(_)) => Some((
+                text_width.max(meter.width),
+                text_height + METER_SPACING + meter.height,
+            )),
+            _ => Some((text_width, text_height)),
+        }
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        self.text_widget.paint(buffer, x, y);
+
+        let (text_height, Some(fraction)) = self.measured else {
+            return;
+        };
+        let meter_y = y as f32 + text_height + METER_SPACING;
+
+        match &self.display {
+            MonitorDisplay::Bar(meter) => {
+                if let Some(area) = Rect::from_xywh(x as f32, meter_y, meter.width, meter.height) {
+                    draw_bar(buffer, area, meter, fraction);
+                }
+            }
+            MonitorDisplay::Sparkline(meter) => {
+                if let Some(area) = Rect::from_xywh(x as f32, meter_y, meter.width, meter.height) {
+                    draw_sparkline(buffer, area, meter, &self.state.read().history);
+                }
+            }
+            MonitorDisplay::Text => {}
+        }
+    }
+}
+
+fn meter_paint(meter: &MeterConfig, alpha: f32) -> Paint<'static> {
+    let (r, g, b, a) = meter.color;
+    let mut color = Color::from_rgba8(r, g, b, a);
+    color.set_alpha(color.alpha() * alpha);
+
+    let mut paint = Paint {
+        anti_alias: true,
+        ..Default::default()
+    };
+    paint.set_color(color);
+    paint
+}
+
+pub(super) fn draw_bar(buffer: &mut Pixmap, area: Rect, meter: &MeterConfig, fraction: f32) {
+    buffer.fill_rect(area, &meter_paint(meter, 0.3), Transform::identity(), None);
+
+    let filled = Rect::from_xywh(
+        area.x(),
+        area.y(),
+        area.width() * fraction.clamp(0.0, 1.0),
+        area.height(),
+    );
+    if let Some(filled) = filled {
+        buffer.fill_rect(
+            filled,
+            &meter_paint(meter, 1.0),
+            Transform::identity(),
+            None,
+        );
+    }
+}
+
+fn draw_sparkline(buffer: &mut Pixmap, area: Rect, meter: &MeterConfig, history: &History) {
+    let (r, g, b, a) = meter.color;
+
+    let chart = Chart {
+        style: GraphStyle::Line,
+        color: Color::from_rgba8(r, g, b, a),
+        fill_color: Color::TRANSPARENT,
+        line_width: 2.0,
+        smooth: false,
+        min: Some(0.0),
+        max: Some(1.0),
+    };
+
+    chart.draw(buffer, area, history);
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CpuTimes {
+    idle: u64,
+    total: u64,
+}
+
+/// Reads a metric from procfs, keeping whatever state is needed between samples.
+pub(super) struct Sampler {
+    metric: Metric,
+    root: PathBuf,
+    previous_cpu: Option<CpuTimes>,
+}
+
+impl Sampler {
+    pub(super) fn new(metric: Metric, root: PathBuf) -> Self {
+        Self {
+            metric,
+            root,
+            previous_cpu: None,
+        }
+    }
+
+    pub(super) fn sample(&mut self) -> Option<Reading> {
+        match self.metric {
+            Metric::Cpu => {
+                let current = read_cpu_times(&self.root)?;
+                let previous = self.previous_cpu.replace(current);
+
+                // Usage is measured between samples, so the first one reports the average since
+                // boot instead.
+                let (idle, total) = match previous {
+                    Some(previous) => (
+                        current.idle.saturating_sub(previous.idle),
+                        current.total.saturating_sub(previous.total),
+                    ),
+                    None => (current.idle, current.total),
+                };
+
+                let usage = match total {
+                    0 => 0.0,
+                    _ => 1.0 - idle as f32 / total as f32,
+                };
+
+                Some(Reading {
+                    fields: vec![("usage", format!("{:.0}", usage * 100.0))],
+                    fraction: Some(usage),
+                })
+            }
+
+            Metric::Memory => {
+                let meminfo = std::fs::read_to_string(self.root.join("meminfo")).ok()?;
+                let field = |name: &str| {
+                    meminfo
+                        .lines()
+                        .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+                        .and_then(|value| value.split_whitespace().next()?.parse::<f64>().ok())
+                        // meminfo reports kibibytes
+                        .map(|kib| kib * 1024.0)
+                };
+
+                let total = field("MemTotal")?;
+                let available = field("MemAvailable").or_else(|| field("MemFree"))?;
+                let used = (total - available).max(0.0);
+                let fraction = match total > 0.0 {
+                    true => (used / total) as f32,
+                    false => 0.0,
+                };
+
+                Some(Reading {
+                    fields: vec![
+                        ("used", human_bytes(used)),
+                        ("total", human_bytes(total)),
+                        ("percent", format!("{:.0}", fraction * 100.0)),
+                    ],
+                    fraction: Some(fraction),
+                })
+            }
+
+            Metric::LoadAverage => {
+                let loadavg = std::fs::read_to_string(self.root.join("loadavg")).ok()?;
+                let mut loads = loadavg.split_whitespace();
+                let one = loads.next()?;
+                let five = loads.next()?;
+                let fifteen = loads.next()?;
+
+                // A load equal to the number of CPUs means the machine is fully busy.
+                let cpus = std::thread::available_parallelism()
+                    .map(|cpus| cpus.get())
+                    .unwrap_or(1);
+                let fraction = one.parse::<f32>().ok().map(|load| load / cpus as f32);
+
+                Some(Reading {
+                    fields: vec![
+                        ("1m", one.to_string()),
+                        ("5m", five.to_string()),
+                        ("15m", fifteen.to_string()),
+                    ],
+                    fraction,
+                })
+            }
+
+            Metric::Uptime => {
+                let uptime = std::fs::read_to_string(self.root.join("uptime")).ok()?;
+                let seconds = uptime.split_whitespace().next()?.parse::<f64>().ok()? as u64;
+
+                let (days, hours, minutes) =
+                    (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60);
+                let uptime = match days {
+                    0 => format!("{hours}h {minutes}m"),
+                    _ => format!("{days}d {hours}h {minutes}m"),
+                };
+
+                Some(Reading {
+                    fields: vec![("uptime", uptime)],
+                    fraction: None,
+                })
+            }
+        }
+    }
+}
+
+fn read_cpu_times(root: &Path) -> Option<CpuTimes> {
+    let stat = std::fs::read_to_string(root.join("stat")).ok()?;
+    let line = stat.lines().find(|line| line.starts_with("cpu "))?;
+
+    // user nice system idle iowait irq softirq steal, guest time is already part of user time.
+    let times: Vec<u64> = line
+        .split_whitespace()
+        .skip(1)
+        .take(8)
+        .filter_map(|time| time.parse().ok())
+        .collect();
+
+    if times.len() < 4 {
+        return None;
+    }
+
+    Some(CpuTimes {
+        idle: times[3] + times.get(4).copied().unwrap_or_default(),
+        total: times.iter().sum(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    /// A procfs root holding the given files.
+    fn procfs(test: &str, files: &[(&str, &str)]) -> TempDir {
+        let root = TempDir::new(&format!("system-{test}"));
+
+        for (name, contents) in files {
+            std::fs::write(root.join(name), contents).unwrap();
+        }
+
+        root
+    }
+
+    fn field<'a>(reading: &'a Reading, name: &str) -> &'a str {
+        reading
+            .fields
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap()
+    }
+
+    #[test]
+    fn cpu_usage_is_measured_between_samples() {
+        let root = procfs(
+            "cpu",
+            &[(
+                "stat",
+                "cpu  100 0 100 700 100 0 0 0 50 0\ncpu0 100 0 100 700 100 0 0 0 50 0\n",
+            )],
+        );
+        let mut sampler = Sampler::new(Metric::Cpu, root.to_path_buf());
+
+        // The first sample is the average since boot, iowait counting as idle.
+        let reading = sampler.sample().unwrap();
+        assert_eq!(field(&reading, "usage"), "20");
+
+        std::fs::write(root.join("stat"), "cpu  225 0 200 775 100 0 0 0 50 0\n").unwrap();
+        let reading = sampler.sample().unwrap();
+        assert_eq!(field(&reading, "usage"), "75");
+
+        // Nothing happened since the last sample.
+        let reading = sampler.sample().unwrap();
+        assert_eq!(reading.fraction, Some(0.0));
+    }
+
+    #[test]
+    fn cpu_sample_needs_the_cpu_line() {
+        let root = procfs("cpu-missing", &[("stat", "cpu0 1 2 3 4\nintr 5\n")]);
+
+        assert!(Sampler::new(Metric::Cpu, root.to_path_buf())
+            .sample()
+            .is_none());
+        assert!(Sampler::new(Metric::Cpu, PathBuf::from("/nonexistent"))
+            .sample()
+            .is_none());
+    }
+
+    #[test]
+    fn memory_usage_prefers_available_memory() {
+        let root = procfs(
+            "memory",
+            &[(
+                "meminfo",
+                "MemTotal:        8000000 kB\nMemFree:         1000000 kB\n\
+                 MemAvailable:    2000000 kB\n",
+            )],
+        );
+
+        let reading = Sampler::new(Metric::Memory, root.to_path_buf())
+            .sample()
+            .unwrap();
+
+        assert_eq!(field(&reading, "used"), human_bytes(6_144_000_000.0));
+        assert_eq!(field(&reading, "total"), human_bytes(8_192_000_000.0));
+        assert_eq!(field(&reading, "percent"), "75");
+        assert_eq!(reading.fraction, Some(0.75));
+    }
+
+    #[test]
+    fn memory_usage_falls_back_to_free_memory() {
+        let root = procfs(
+            "memory-free",
+            &[("meminfo", "MemTotal: 4000 kB\nMemFree: 1000 kB\n")],
+        );
+        let reading = Sampler::new(Metric::Memory, root.to_path_buf())
+            .sample()
+            .unwrap();
+        assert_eq!(field(&reading, "percent"), "75");
+
+        let root = procfs("memory-missing", &[("meminfo", "MemFree: 1000 kB\n")]);
+        assert!(Sampler::new(Metric::Memory, root.to_path_buf())
+            .sample()
+            .is_none());
+    }
+
+    #[test]
+    fn load_average_reports_the_three_loads() {
+        let root = procfs("loadavg", &[("loadavg", "0.50 1.25 2.00 1/234 5678\n")]);
+
+        let reading = Sampler::new(Metric::LoadAverage, root.to_path_buf())
+            .sample()
+            .unwrap();
+
+        assert_eq!(field(&reading, "1m"), "0.50");
+        assert_eq!(field(&reading, "5m"), "1.25");
+        assert_eq!(field(&reading, "15m"), "2.00");
+        assert!(reading.fraction.is_some_and(|fraction| fraction > 0.0));
+
+        let root = procfs("loadavg-short", &[("loadavg", "0.50 1.25\n")]);
+        assert!(Sampler::new(Metric::LoadAverage, root.to_path_buf())
+            .sample()
+            .is_none());
+    }
+
+    #[test]
+    fn uptime_is_shown_in_days_hours_and_minutes() {
+        let root = procfs("uptime", &[("uptime", "90061.42 1234.56\n")]);
+        let reading = Sampler::new(Metric::Uptime, root.to_path_buf())
+            .sample()
+            .unwrap();
+        assert_eq!(field(&reading, "uptime"), "1d 1h 1m");
+        assert_eq!(reading.fraction, None);
+
+        let root = procfs("uptime-short", &[("uptime", "3599.99 10.00\n")]);
+        let reading = Sampler::new(Metric::Uptime, root.to_path_buf())
+            .sample()
+            .unwrap();
+        assert_eq!(field(&reading, "uptime"), "0h 59m");
+    }
+}
@@ -1,3 +1,4 @@
+use cosmic_text::Align;
 use cosmic_text::Attrs;
 use cosmic_text::AttrsOwned;
 use cosmic_text::Buffer;
@@ -8,17 +9,20 @@ use cosmic_text::Metrics;
 use cosmic_text::Shaping;
 use cosmic_text::SwashCache;
 use cosmic_text::Weight;
+use cosmic_text::Wrap;
 use tiny_skia::Paint;
 use tiny_skia::Pixmap;
 use tiny_skia::Rect;
 use tiny_skia::Transform;
-use winit::window::Window;
+use winit::dpi::PhysicalSize;
 
+use crate::config::Overflow;
+use crate::config::TextAlign;
 use crate::config::TextConfig;
-use crate::render::DrawError;
-use crate::render::Drawable;
+use crate::config::WrapMode;
 
 use super::Position;
+use super::Widget;
 use super::WidgetError;
 
 pub struct Text {
@@ -26,6 +30,15 @@ pub struct Text {
     font_system: FontSystem,
     swash_cache: SwashCache,
     font_attrs: AttrsOwned,
+    color: Color,
+    /// Font size and line height from the configuration, before any shrinking.
+    metrics: Metrics,
+    overflow: Overflow,
+    max_width: Option<f32>,
+    max_height: Option<f32>,
+    /// `None` keeps the lines against the left edge, or the right one for right-to-left text.
+    align: Option<Align>,
+    wrap: Wrap,
 
     data: String,
 
@@ -52,10 +65,8 @@ impl Text {
         let attrs = AttrsOwned::new(attrs);
 
         // TODO how this line height works???
-        let mut buffer = Buffer::new(
-            &mut font_system,
-            Metrics::new(config.font.font_size, config.font.line_height),
-        );
+        let metrics = Metrics::new(config.font.font_size, config.font.line_height);
+        let mut buffer = Buffer::new(&mut font_system, metrics);
         buffer.set_text(
             &mut font_system,
             &config.text,
@@ -63,11 +74,32 @@ impl Text {
             Shaping::Advanced,
         );
 
+        let (r, g, b, a) = config.font.color;
+
+        let align = match config.layout.align {
+            TextAlign::Left => None,
+            TextAlign::Center => Some(Align::Center),
+            TextAlign::Right => Some(Align::Right),
+            TextAlign::Justified => Some(Align::Justified),
+        };
+        let wrap = match config.layout.wrap {
+            WrapMode::Word => Wrap::WordOrGlyph,
+            WrapMode::Glyph => Wrap::Glyph,
+            WrapMode::None => Wrap::None,
+        };
+
         Ok(Self {
             buffer,
             font_system,
             swash_cache,
             font_attrs: attrs,
+            color: Color::rgba(r, g, b, a),
+            metrics,
+            overflow: config.font.overflow,
+            max_width: config.layout.max_width,
+            max_height: config.layout.max_height,
+            align,
+            wrap,
             position: config.position,
             data: config.text.clone(),
         })
@@ -76,10 +108,40 @@ impl Text {
     pub(super) fn update_data(&mut self, data: String) {
         self.data = data;
     }
-}
 
-impl Drawable for Text {
-    fn draw(&mut self, window: &Window, buffer: &mut Pixmap) -> Result<(), DrawError> {
+    pub(super) fn set_color(&mut self, color: Color) {
+        self.color = color;
+    }
+
+    pub(super) fn color(&self) -> Color {
+        self.color
+    }
+
+    pub(super) fn font_size(&self) -> f32 {
+        self.buffer.metrics().font_size
+    }
+
+    /// Shapes the current data to fit in `space` and the maximum size, following the overflow
+    /// policy, and returns the width and height of the laid out text.
+    pub(super) fn layout(&mut self, (space_width, space_height): (f32, f32)) -> (f32, f32) {
+        let max_width = self
+            .max_width
+            .map_or(space_width, |max| max.min(space_width));
+        let max_height = self
+            .max_height
+            .map_or(space_height, |max| max.min(space_height));
+
+        // Lines to ellipsize are kept whole, `ellipsize` cuts them itself.
+        let (width, wrap) = match self.overflow {
+            Overflow::Wrap => (Some(max_width), self.wrap),
+            Overflow::Ellipsize => (None, Wrap::None),
+            _ => (self.max_width, self.wrap),
+        };
+        // The maximum height is applied once the overflow policy is done, cosmic-text leaves out
+        // the lines below it which would hide the overflow from `shrink`.
+        self.buffer
+            .set_metrics_and_size(&mut self.font_system, self.metrics, width, None);
+        self.buffer.set_wrap(&mut self.font_system, wrap);
         self.buffer.set_text(
             &mut self.font_system,
             &self.data,
@@ -87,73 +149,344 @@ impl Drawable for Text {
             Shaping::Advanced,
         );
 
-        let mut paint = Paint {
-            anti_alias: true,
-            ..Default::default()
+        let (width, height) = self.size();
+        let overflows = width > max_width || height > max_height;
+
+        let (width, height) = match self.overflow {
+            Overflow::Shrink if overflows => self.shrink(max_width, max_height, (width, height)),
+            Overflow::Ellipsize if width > max_width => self.ellipsize(max_width),
+            _ => (width, height),
         };
 
-        let (padding_x, padding_y) = match self.position {
-            Position::Center => {
-                let (width, height) = self
-                    .buffer
-                    .layout_runs()
-                    .fold((0.0, 0.0), |(width, height), run| {
-                        (run.line_w.max(width), height + 1.0)
-                    });
+        match self.align {
+            Some(align) => self.align(align, width),
+            None if self.max_height.is_some_and(|max| height > max) => {
+                let (width, _) = self.buffer.size();
+                self.buffer
+                    .set_size(&mut self.font_system, width, self.max_height);
 
-                let height = height * self.buffer.metrics().line_height;
+                self.size()
+            }
+            None => (width, height),
+        }
+    }
 
-                let centered_x = (window.inner_size().width / 2) - (width / 2.0) as u32;
-                let centered_y = (window.inner_size().height / 2) - (height / 2.0) as u32;
+    /// Lines the text up inside its widest line, `width`, which is the width it is measured and
+    /// placed with.
+    fn align(&mut self, align: Align, width: f32) -> (f32, f32) {
+        // Lines are broken the same way since none is wider than the new width.
+        self.buffer
+            .set_size(&mut self.font_system, Some(width), self.max_height);
 
-                (centered_x as i32, centered_y as i32)
-            }
+        for line in self.buffer.lines.iter_mut() {
+            line.set_align(Some(align));
+        }
+        self.buffer.shape_until_scroll(&mut self.font_system, false);
 
-            Position::XY { x, y } => (x as i32, y as i32),
+        self.size()
+    }
+
+    /// Width and height of the laid out text.
+    fn size(&self) -> (f32, f32) {
+        let (width, lines) = self
+            .buffer
+            .layout_runs()
+            .fold((0.0, 0.0), |(width, lines), run| {
+                (run.line_w.max(width), lines + 1.0)
+            });
 
-            Position::CenteredX { y } => {
-                let width = self
-                    .buffer
-                    .layout_runs()
-                    .fold(0.0, |width, run| run.line_w.max(width));
+        (width, lines * self.buffer.metrics().line_height)
+    }
 
-                let centered_x = (window.inner_size().width / 2) - (width / 2.0) as u32;
+    /// Makes the font smaller until the text fits in the given size.
+    fn shrink(
+        &mut self,
+        max_width: f32,
+        max_height: f32,
+        (width, height): (f32, f32),
+    ) -> (f32, f32) {
+        const MIN_FONT_SIZE: f32 = 1.0;
 
-                (centered_x as i32, y as i32)
-            }
+        let mut scale = (max_width / width).min(max_height / height);
+        let mut size = (width, height);
 
-            Position::CenteredY { x } => {
-                let height = self.buffer.layout_runs().count();
-                let height = height as f32 * self.buffer.metrics().line_height;
+        // Glyphs don't scale exactly with the font size, a few smaller steps may be needed.
+        for _ in 0..8 {
+            let font_size = (self.metrics.font_size * scale).max(MIN_FONT_SIZE);
+            let line_height = self.metrics.line_height * font_size / self.metrics.font_size;
 
-                let centered_y = (window.inner_size().height / 2) - (height / 2.0) as u32;
+            self.buffer
+                .set_metrics(&mut self.font_system, Metrics::new(font_size, line_height));
+            size = self.size();
 
-                (x as i32, centered_y as i32)
+            if (size.0 <= max_width && size.1 <= max_height) || font_size == MIN_FONT_SIZE {
+                break;
             }
+            scale *= 0.95;
+        }
+
+        size
+    }
+
+    /// Cuts every line wider than `max_width` and ends it with an ellipsis.
+    fn ellipsize(&mut self, max_width: f32) -> (f32, f32) {
+        const ELLIPSIS: &str = "\u{2026}";
+
+        let mut ellipsis = Buffer::new(&mut self.font_system, self.metrics);
+        ellipsis.set_text(
+            &mut self.font_system,
+            ELLIPSIS,
+            self.font_attrs.as_attrs(),
+            Shaping::Advanced,
+        );
+        let ellipsis_width = ellipsis
+            .layout_runs()
+            .map(|run| run.line_w)
+            .fold(0.0, f32::max);
+
+        let lines: Vec<String> = self
+            .buffer
+            .layout_runs()
+            .map(|run| {
+                // `run.text` is the whole line, the run only covers its glyphs.
+                let (Some(first), Some(last)) = (run.glyphs.first(), run.glyphs.last()) else {
+                    return String::new();
+                };
+                let start = first.start.min(last.start);
+                let end = first.end.max(last.end);
+
+                if run.line_w <= max_width {
+                    return run.text[start..end].to_string();
+                }
+
+                let cut = run
+                    .glyphs
+                    .iter()
+                    .find(|glyph| glyph.x + glyph.w > max_width - ellipsis_width)
+                    .map_or(end, |glyph| glyph.start);
+
+                format!("{}{ELLIPSIS}", run.text[start..cut.max(start)].trim_end())
+            })
+            .collect();
+
+        self.buffer.set_text(
+            &mut self.font_system,
+            &lines.join("\n"),
+            self.font_attrs.as_attrs(),
+            Shaping::Advanced,
+        );
+
+        self.size()
+    }
+
+    /// Draws the last laid out text with its top-left corner at `(x, y)`.
+    pub(super) fn paint(&mut self, buffer: &mut Pixmap, x: i32, y: i32) {
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Default::default()
         };
 
         self.buffer.draw(
             &mut self.font_system,
             &mut self.swash_cache,
-            // TODO: make this color configurable
-            Color::rgba(255, 255, 255, 100),
-            |x, y, w, h, color| {
+            self.color,
+            |glyph_x, glyph_y, w, h, color| {
                 paint.set_color_rgba8(color.r(), color.g(), color.b(), color.a());
-                buffer.fill_rect(
-                    Rect::from_xywh(
-                        (x + padding_x) as f32,
-                        (y + padding_y) as f32,
-                        w as f32,
-                        h as f32,
-                    )
-                    .unwrap(),
-                    &paint,
-                    Transform::identity(),
-                    None,
+
+                // Pixels outside of the window are left out by tiny-skia.
+                let area = Rect::from_xywh(
+                    (glyph_x + x) as f32,
+                    (glyph_y + y) as f32,
+                    w as f32,
+                    h as f32,
                 );
+                if let Some(area) = area {
+                    buffer.fill_rect(area, &paint, Transform::identity(), None);
+                }
             },
         );
+    }
+}
+
+impl Widget for Text {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        Some(self.layout(space))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        self.paint(buffer, x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Font;
+    use crate::config::TextLayout;
+
+    use super::*;
+
+    const SPACE: (f32, f32) = (1000.0, 1000.0);
+
+    fn text(data: &str, overflow: Overflow, layout: TextLayout) -> Text {
+        let font = Font {
+            line_height: 20.0,
+            font_family: None,
+            font_size: 16.0,
+            font_weight: 400,
+            color: (255, 255, 255, 255),
+            overflow,
+        };
+
+        Text::new(TextConfig {
+            layout,
+            ..TextConfig::new(data.to_string(), Position::default(), font)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn shrink_fits_lines_below_the_maximum_height() {
+        let layout = TextLayout {
+            max_height: Some(30.0),
+            ..Default::default()
+        };
+        let mut text = text("one\ntwo\nthree", Overflow::Shrink, layout);
+
+        let (_, height) = text.layout(SPACE);
+
+        assert!(height <= 30.0, "{height}");
+        assert!(text.font_size() < 16.0);
+    }
+
+    #[test]
+    fn clip_leaves_out_lines_below_the_maximum_height() {
+        let layout = TextLayout {
+            max_height: Some(30.0),
+            ..Default::default()
+        };
+        let mut text = text("one\ntwo\nthree", Overflow::Clip, layout);
+
+        let (_, height) = text.layout(SPACE);
+
+        assert!(height < 60.0, "{height}");
+        assert_eq!(text.font_size(), 16.0);
+    }
+
+    /// Text of the laid out lines.
+    fn lines(text: &Text) -> Vec<String> {
+        text.buffer
+            .layout_runs()
+            .map(|run| run.text.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn clip_keeps_text_wider_than_the_space() {
+        let mut text = text("a fairly long line", Overflow::Clip, TextLayout::default());
+
+        let (width, height) = text.layout((40.0, 10.0));
+
+        assert!(width > 40.0, "{width}");
+        assert_eq!(height, 20.0);
+        assert_eq!(lines(&text), ["a fairly long line"]);
+    }
+
+    #[test]
+    fn wrap_breaks_lines_to_the_space_width() {
+        let mut text = text("a fairly long line", Overflow::Wrap, TextLayout::default());
+
+        let (width, height) = text.layout((80.0, 1000.0));
+
+        assert!(width <= 80.0, "{width}");
+        assert!(height > 20.0, "{height}");
+        assert_eq!(text.font_size(), 16.0);
+    }
+
+    #[test]
+    fn shrink_fits_the_space() {
+        let mut text = text(
+            "a fairly long line",
+            Overflow::Shrink,
+            TextLayout::default(),
+        );
+
+        let (width, height) = text.layout((80.0, 1000.0));
+
+        assert!(width <= 80.0, "{width}");
+        assert!(height < 20.0, "{height}");
+        assert!(text.font_size() < 16.0);
+    }
+
+    #[test]
+    fn shrink_stops_at_the_minimum_font_size() {
+        let mut text = text(
+            "a fairly long line",
+            Overflow::Shrink,
+            TextLayout::default(),
+        );
+
+        let (width, _) = text.layout((1.0, 1.0));
+
+        assert!(width > 1.0, "{width}");
+        assert_eq!(text.font_size(), 1.0);
+
+        // Nothing fits in an empty space either.
+        text.layout((0.0, 0.0));
+        assert_eq!(text.font_size(), 1.0);
+    }
+
+    #[test]
+    fn ellipsize_cuts_lines_wider_than_the_space() {
+        let mut text = text(
+            "a fairly long line\nshort",
+            Overflow::Ellipsize,
+            TextLayout::default(),
+        );
+
+        let (width, _) = text.layout((80.0, 1000.0));
+
+        assert!(width <= 80.0, "{width}");
+        let lines = lines(&text);
+        assert!(lines[0].starts_with("a fair"), "{lines:?}");
+        assert!(lines[0].ends_with('\u{2026}'), "{lines:?}");
+        assert_eq!(lines[1], "short");
+    }
+
+    #[test]
+    fn ellipsize_leaves_only_the_ellipsis_when_the_first_glyph_overflows() {
+        let mut text = text("wide", Overflow::Ellipsize, TextLayout::default());
+
+        text.layout((2.0, 1000.0));
+
+        assert_eq!(lines(&text), ["\u{2026}"]);
+    }
+
+    #[test]
+    fn ellipsize_cuts_lines_at_the_maximum_width_instead_of_wrapping() {
+        let layout = TextLayout {
+            max_width: Some(80.0),
+            ..Default::default()
+        };
+        let mut text = text("a fairly long line", Overflow::Ellipsize, layout);
+
+        let (width, height) = text.layout(SPACE);
+        assert!(width <= 80.0, "{width}");
+        assert_eq!(height, 20.0);
+        let cut = lines(&text);
+        assert_eq!(cut.len(), 1, "{cut:?}");
+        assert!(
+            cut[0].starts_with("a fair") && cut[0].ends_with('\u{2026}'),
+            "{cut:?}"
+        );
 
-        Ok(())
+        // A space narrower than the maximum width cuts the line shorter, still only once.
+        text.layout((50.0, 1000.0));
+        let shorter = lines(&text);
+        assert_eq!(shorter.len(), 1, "{shorter:?}");
+        assert!(shorter[0].len() < cut[0].len(), "{shorter:?}");
     }
 }
@@ -0,0 +1,550 @@
+use std::f32::consts::PI;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use directories::ProjectDirs;
+use serde::Deserialize;
+use spin::RwLock;
+use tiny_skia::Color;
+use tiny_skia::FillRule;
+use tiny_skia::LineCap;
+use tiny_skia::Paint;
+use tiny_skia::PathBuilder;
+use tiny_skia::Pixmap;
+use tiny_skia::Stroke;
+use tiny_skia::Transform;
+use winit::dpi::PhysicalSize;
+use winit::event_loop::EventLoop;
+
+use crate::config::TextConfig;
+use crate::config::WeatherConfig;
+
+use super::http;
+use super::http::FetchError;
+use super::text::Text;
+use super::Position;
+use super::Widget;
+use super::WidgetError;
+
+/// Space between the icon and the text.
+const ICON_SPACING: f32 = 16.0;
+
+/// The parts of an Open-Meteo forecast response the widget uses.
+#[derive(Debug, Clone, Deserialize)]
+struct Forecast {
+    current: CurrentWeather,
+    #[serde(default)]
+    current_units: CurrentUnits,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CurrentWeather {
+    temperature_2m: f32,
+    weather_code: u8,
+    #[serde(default = "default_is_day")]
+    is_day: u8,
+}
+
+fn default_is_day() -> u8 {
+    1
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CurrentUnits {
+    temperature_2m: String,
+}
+
+impl Default for CurrentUnits {
+    fn default() -> Self {
+        Self {
+            temperature_2m: "°C".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Thunderstorm,
+    Unknown,
+}
+
+impl Condition {
+    /// Maps a WMO weather interpretation code to a condition.
+    fn from_code(code: u8) -> Self {
+        match code {
+            0 => Condition::Clear,
+            1 | 2 => Condition::PartlyCloudy,
+            3 => Condition::Cloudy,
+            45 | 48 => Condition::Fog,
+            51..=57 => Condition::Drizzle,
+            61..=67 | 80..=82 => Condition::Rain,
+            71..=77 | 85 | 86 => Condition::Snow,
+            95..=99 => Condition::Thunderstorm,
+            _ => Condition::Unknown,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Condition::Clear => "Clear",
+            Condition::PartlyCloudy => "Partly cloudy",
+            Condition::Cloudy => "Cloudy",
+            Condition::Fog => "Fog",
+            Condition::Drizzle => "Drizzle",
+            Condition::Rain => "Rain",
+            Condition::Snow => "Snow",
+            Condition::Thunderstorm => "Thunderstorm",
+            Condition::Unknown => "Unknown",
+        }
+    }
+}
+
+pub struct Weather {
+    text_widget: Text,
+    position: Position,
+    format: String,
+    forecast: Arc<RwLock<Option<Forecast>>>,
+    /// Condition, whether it is day and height of the text, as last measured.
+    measured: Option<(Condition, bool, f32)>,
+}
+
+impl Weather {
+    pub fn new(event_loop: &EventLoop<()>, config: WeatherConfig) -> Result<Self, WidgetError> {
+        let text_config = TextConfig::new(String::new(), config.position.clone(), config.font);
+        let widget = Text::new(text_config)?;
+
+        let cache = config.cache.map(PathBuf::from).or_else(|| {
+            ProjectDirs::from("rs", "", "russ").map(|dirs| dirs.cache_dir().join("weather.json"))
+        });
+
+        // Show whatever was fetched last time until the first request completes.
+        let cached = cache.as_deref().and_then(read_cache);
+        let forecast = Arc::new(RwLock::new(cached));
+
+        {
+            let event_loop_proxy = event_loop.create_proxy();
+            let forecast = forecast.clone();
+            let interval = Duration::from_secs(config.interval.max(1));
+            let url = config.url;
+            let (latitude, longitude) = (config.latitude, config.longitude);
+
+            std::thread::spawn(move || loop {
+                match refresh(&url, latitude, longitude, cache.as_deref()) {
+                    Ok(fetched) => {
+                        *forecast.write() = Some(fetched);
+                        event_loop_proxy.send_event(()).unwrap();
+                    }
+
+                    // The last forecast stays on screen until a request succeeds again.
+                    Err(err) => println!("Error fetching weather: {err}"),
+                }
+
+                std::thread::sleep(interval);
+            });
+        }
+
+        Ok(Self {
+            text_widget: widget,
+            position: config.position,
+            format: config.format,
+            forecast,
+            measured: None,
+        })
+    }
+}
+
+impl Widget for Weather {
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn measure(&mut self, _window: PhysicalSize<u32>, space: (f32, f32)) -> Option<(f32, f32)> {
+        let forecast = self.forecast.read().clone()?;
+
+        let condition = Condition::from_code(forecast.current.weather_code);
+        let text = self
+            .format
+            .replace(
+                "{temperature}",
+                &format!("{:.0}", forecast.current.temperature_2m),
+            )
+            .replace("{unit}", &forecast.current_units.temperature_2m)
+            .replace("{condition}", condition.label());
+        self.text_widget.update_data(text);
+
+        let (text_width, text_height) = self.text_widget.layout(space);
+        let icon_size = self.text_widget.font_size();
+        self.measured = Some((condition, forecast.current.is_day != 0, text_height));
+
+        Some((
+            icon_size + ICON_SPACING + text_width,
+            icon_size.max(text_height),
+        ))
+    }
+
+    fn draw_at(&mut self, _window: PhysicalSize<u32>, buffer: &mut Pixmap, (x, y): (i32, i32)) {
+        let Some((condition, is_day, text_height)) = self.measured else {
+            return;
+        };
+
+        let icon_size = self.text_widget.font_size();
+        let height = icon_size.max(text_height);
+
+        let color = self.text_widget.color();
+        let icon = Icon {
+            x: x as f32,
+            y: y as f32 + (height - icon_size) / 2.0,
+            size: icon_size,
+            color: Color::from_rgba8(color.r(), color.g(), color.b(), color.a()),
+        };
+        icon.draw(buffer, condition, is_day);
+
+        self.text_widget.paint(
+            buffer,
+            (x as f32 + icon_size + ICON_SPACING) as i32,
+            (y as f32 + (height - text_height) / 2.0) as i32,
+        );
+    }
+}
+
+/// Requests the current weather, saving the response to `cache` when it is valid.
+fn refresh(
+    url: &str,
+    latitude: f64,
+    longitude: f64,
+    cache: Option<&Path>,
+) -> Result<Forecast, FetchError> {
+    let request = ureq::get(url)
+        .query("latitude", &latitude.to_string())
+        .query("longitude", &longitude.to_string())
+        .query("current", "temperature_2m,weather_code,is_day");
+    let body = http::get(request)?;
+
+    let forecast = serde_json::from_str(&body)?;
+
+    if let Some(cache) = cache {
+        if let Some(parent) = cache.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(cache, body);
+    }
+
+    Ok(forecast)
+}
+
+/// The forecast saved by the last successful request, if it can still be read.
+fn read_cache(cache: &Path) -> Option<Forecast> {
+    let body = std::fs::read_to_string(cache).ok()?;
+
+    serde_json::from_str(&body).ok()
+}
+
+/// Square area an icon is drawn in, icons are made of simple shapes scaled to its size.
+struct Icon {
+    x: f32,
+    y: f32,
+    size: f32,
+    color: Color,
+}
+
+impl Icon {
+    fn draw(&self, buffer: &mut Pixmap, condition: Condition, is_day: bool) {
+        match condition {
+            Condition::Clear if is_day => self.sun(buffer, 0.5, 0.5, 0.2),
+            Condition::Clear => self.moon(buffer),
+            Condition::PartlyCloudy => {
+                if is_day {
+                    self.sun(buffer, 0.62, 0.38, 0.15);
+                }
+                self.cloud(buffer, 0.15);
+            }
+            Condition::Cloudy | Condition::Unknown => self.cloud(buffer, 0.0),
+            Condition::Fog => {
+                for row in [0.35, 0.5, 0.65] {
+                    self.line(buffer, (0.15, row), (0.85, row));
+                }
+            }
+            Condition::Drizzle | Condition::Rain => {
+                self.cloud(buffer, -0.12);
+                let drops: &[f32] = match condition {
+                    Condition::Drizzle => &[0.4, 0.6],
+                    _ => &[0.3, 0.5, 0.7],
+                };
+                for &x in drops {
+                    self.line(buffer, (x, 0.72), (x - 0.06, 0.88));
+                }
+            }
+            Condition::Snow => {
+                self.cloud(buffer, -0.12);
+                for x in [0.3, 0.5, 0.7] {
+                    self.circle(buffer, x, 0.8, 0.035);
+                }
+            }
+            Condition::Thunderstorm => {
+                self.cloud(buffer, -0.12);
+
+                let mut bolt = PathBuilder::new();
+                bolt.move_to(self.px(0.55), self.py(0.6));
+                bolt.line_to(self.px(0.42), self.py(0.78));
+                bolt.line_to(self.px(0.54), self.py(0.78));
+                bolt.line_to(self.px(0.45), self.py(0.95));
+                self.stroke(buffer, bolt);
+            }
+        }
+    }
+
+    fn px(&self, x: f32) -> f32 {
+        self.x + x * self.size
+    }
+
+    fn py(&self, y: f32) -> f32 {
+        self.y + y * self.size
+    }
+
+    fn paint(&self) -> Paint<'static> {
+        let mut paint = Paint {
+            anti_alias: true,
+            ..Default::default()
+        };
+        paint.set_color(self.color);
+        paint
+    }
+
+    fn stroke(&self, buffer: &mut Pixmap, path: PathBuilder) {
+        let Some(path) = path.finish() else {
+            return;
+        };
+
+        let stroke = Stroke {
+            width: (self.size * 0.06).max(1.0),
+            line_cap: LineCap::Round,
+            ..Default::default()
+        };
+
+        buffer.stroke_path(&path, &self.paint(), &stroke, Transform::identity(), None);
+    }
+
+    fn fill(&self, buffer: &mut Pixmap, path: PathBuilder) {
+        if let Some(path) = path.finish() {
+            buffer.fill_path(
+                &path,
+                &self.paint(),
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    fn line(&self, buffer: &mut Pixmap, from: (f32, f32), to: (f32, f32)) {
+        let mut path = PathBuilder::new();
+        path.move_to(self.px(from.0), self.py(from.1));
+        path.line_to(self.px(to.0), self.py(to.1));
+        self.stroke(buffer, path);
+    }
+
+    fn circle(&self, buffer: &mut Pixmap, x: f32, y: f32, radius: f32) {
+        let mut path = PathBuilder::new();
+        path.push_circle(self.px(x), self.py(y), radius * self.size);
+        self.fill(buffer, path);
+    }
+
+    fn sun(&self, buffer: &mut Pixmap, x: f32, y: f32, radius: f32) {
+        self.circle(buffer, x, y, radius);
+
+        for ray in 0..8 {
+            let angle = ray as f32 * PI / 4.0;
+            let (sin, cos) = angle.sin_cos();
+            self.line(
+                buffer,
+                (x + cos * radius * 1.4, y + sin * radius * 1.4),
+                (x + cos * radius * 1.9, y + sin * radius * 1.9),
+            );
+        }
+    }
+
+    /// Crescent left of a disc once the part covered by a smaller, offset one is taken out.
+    fn moon(&self, buffer: &mut Pixmap) {
+        const SEGMENTS: usize = 32;
+
+        let (x, y, radius) = (0.5, 0.5, 0.3);
+        let (shadow_x, shadow_y, shadow_radius) = (0.62, 0.4, 0.24);
+
+        let (dx, dy) = (shadow_x - x, shadow_y - y);
+        let distance = f32::hypot(dx, dy);
+        let toward = dy.atan2(dx);
+        // Distance from the center of the disc to the line through both points where the circles
+        // cross, then how far around each circle those points are.
+        let chord = (distance * distance + radius * radius - shadow_radius * shadow_radius)
+            / (2.0 * distance);
+        let half = (chord / radius).acos();
+        let shadow_half = ((distance - chord) / shadow_radius).acos();
+
+        let point = |(x, y, radius): (f32, f32, f32), angle: f32| {
+            (
+                self.px(x + radius * angle.cos()),
+                self.py(y + radius * angle.sin()),
+            )
+        };
+
+        // Around the disc away from the shadow, then back along the edge of the shadow.
+        let mut path = PathBuilder::new();
+        let (start_x, start_y) = point((x, y, radius), toward + half);
+        path.move_to(start_x, start_y);
+
+        for segment in 1..=SEGMENTS {
+            let fraction = segment as f32 / SEGMENTS as f32;
+            let (px, py) = point(
+                (x, y, radius),
+                toward + half + fraction * (2.0 * (PI - half)),
+            );
+            path.line_to(px, py);
+        }
+        for segment in 1..=SEGMENTS {
+            let fraction = segment as f32 / SEGMENTS as f32;
+            let angle = toward + PI + shadow_half - fraction * 2.0 * shadow_half;
+            let (px, py) = point((shadow_x, shadow_y, shadow_radius), angle);
+            path.line_to(px, py);
+        }
+        path.close();
+
+        self.fill(buffer, path);
+    }
+
+    /// Cloud made of overlapping discs over a flat base, `offset` moves it up or down.
+    fn cloud(&self, buffer: &mut Pixmap, offset: f32) {
+        let mut path = PathBuilder::new();
+        path.push_circle(self.px(0.35), self.py(0.58 + offset), 0.16 * self.size);
+        path.push_circle(self.px(0.55), self.py(0.5 + offset), 0.2 * self.size);
+        path.push_circle(self.px(0.72), self.py(0.6 + offset), 0.14 * self.size);
+        if let Some(base) = tiny_skia::Rect::from_ltrb(
+            self.px(0.2),
+            self.py(0.6 + offset),
+            self.px(0.85),
+            self.py(0.74 + offset),
+        ) {
+            path.push_rect(base);
+        }
+        self.fill(buffer, path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+    use std::io::BufReader;
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    use crate::widget::TempDir;
+
+    use super::*;
+
+    const FORECAST: &str = r#"{
+        "current": { "temperature_2m": 21.4, "weather_code": 2, "is_day": 0 },
+        "current_units": { "temperature_2m": "°F" }
+    }"#;
+
+    /// Answers one request after the other with the given status and body, returning the URL to
+    /// send them to.
+    fn serve(responses: Vec<(u16, &'static str)>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/forecast", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+
+                // Requests have no body, the headers end with an empty line.
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+        });
+
+        url
+    }
+
+    /// A cache file whose directory doesn't exist yet, as on the first run.
+    fn cache(dir: &TempDir) -> PathBuf {
+        dir.join("cache").join("weather.json")
+    }
+
+    #[test]
+    fn forecast_is_cached_and_kept_when_requests_fail() {
+        let url = serve(vec![(200, FORECAST), (500, "oops"), (200, "not json")]);
+        let dir = TempDir::new("weather-fallback");
+        let cache = cache(&dir);
+
+        let forecast = refresh(&url, 1.0, 2.0, Some(&cache)).unwrap();
+        assert_eq!(forecast.current.weather_code, 2);
+        assert_eq!(forecast.current.is_day, 0);
+        assert_eq!(forecast.current_units.temperature_2m, "°F");
+
+        assert!(matches!(
+            refresh(&url, 1.0, 2.0, Some(&cache)),
+            Err(FetchError::Request(_))
+        ));
+        assert!(matches!(
+            refresh(&url, 1.0, 2.0, Some(&cache)),
+            Err(FetchError::Json(_))
+        ));
+
+        // Failed requests leave the last good response in the cache.
+        let cached = read_cache(&cache).unwrap();
+        assert_eq!(cached.current.temperature_2m, 21.4);
+    }
+
+    #[test]
+    fn unreadable_cache_is_ignored() {
+        let dir = TempDir::new("weather-unreadable");
+        let cache = cache(&dir);
+        assert!(read_cache(&cache).is_none());
+
+        std::fs::create_dir_all(cache.parent().unwrap()).unwrap();
+        std::fs::write(&cache, "{").unwrap();
+        assert!(read_cache(&cache).is_none());
+    }
+
+    #[test]
+    fn moon_is_a_crescent() {
+        let mut buffer = Pixmap::new(100, 100).unwrap();
+        let icon = Icon {
+            x: 0.0,
+            y: 0.0,
+            size: 100.0,
+            color: Color::WHITE,
+        };
+
+        icon.draw(&mut buffer, Condition::Clear, false);
+
+        let alpha = |x: u32, y: u32| buffer.pixel(x, y).unwrap().alpha();
+        // The side of the disc away from the shadow.
+        assert_eq!(alpha(28, 60), 255);
+        assert_eq!(alpha(50, 75), 255);
+        // Inside the shadow.
+        assert_eq!(alpha(62, 40), 0);
+        assert_eq!(alpha(55, 45), 0);
+        // The shadow reaching out of the disc isn't drawn.
+        assert_eq!(alpha(79, 26), 0);
+        assert_eq!(alpha(10, 10), 0);
+    }
+}